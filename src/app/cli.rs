@@ -0,0 +1,344 @@
+use crate::app::config_dir;
+use katatui::*;
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+pub const HELP: &str = "\
+loopfetch - a fetch that keeps on fetching
+
+usage: loopfetch [options]
+
+options:
+  -c, --config <path>    with --once, load this lua file instead of init.lua
+                         (the tui always loads init.lua from the config dir)
+  -p, --profile <name>   run profiles/<name>.lua from the config dir on top of init.lua
+      --once             print the fetch a single time and exit
+  -f, --format <fmt>     output of --once: ansi (default), plain or json
+      --no-color         no colors, NO_COLOR does the same
+      --fps <n>          render calls per second
+      --tps <n>          logical updates per second
+      --rps <n>          fetch refreshes per second
+  -l, --layout <name>    horizontal or vertical
+      --logo <name>      auto, none, or a logo name like arch, nixos_small
+      --list-modules     list the fields FETCH offers to init.lua
+  -V, --version          print the version
+  -h, --help             print this help
+";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FORMAT {
+   #[default]
+   Ansi,
+   Plain,
+   Json,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CLI {
+   pub config: Option<PathBuf>,
+   pub profile: Option<String>,
+   pub once: bool,
+   pub format: FORMAT,
+   pub no_color: bool,
+   pub fps: Option<u32>,
+   pub tps: Option<u32>,
+   pub rps: Option<u32>,
+   pub layout: Option<String>,
+   pub logo: Option<String>,
+   pub list_modules: bool,
+   pub version: bool,
+   pub help: bool,
+}
+
+static ARGS: OnceLock<CLI> = OnceLock::new();
+
+// all defaults until `set_cli` is called
+pub fn cli() -> &'static CLI {
+   ARGS.get_or_init(CLI::default)
+}
+
+pub fn set_cli(cli: CLI) {
+   let _ = ARGS.set(cli);
+}
+
+impl CLI {
+   pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+      let mut cli = CLI::default();
+      let mut args = args.into_iter();
+      while let Some(arg) = args.next() {
+         let (flag, inline) = match arg.split_once('=') {
+            Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+            _ => (arg.clone(), None),
+         };
+         let mut value = || match inline.clone().or_else(|| args.next()) {
+            Some(v) => Ok(v),
+            None => Err(format!("{flag} needs a value")),
+         };
+         let number = |v: String| match v.parse::<u32>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("{flag} takes a number above 0, not '{v}'")),
+         };
+         match flag.as_str() {
+            // relative to where loopfetch was started, not to the config dir
+            "-c" | "--config" => {
+               let path = PathBuf::from(value()?);
+               cli.config = Some(std::path::absolute(&path).unwrap_or(path));
+            }
+            "-p" | "--profile" => cli.profile = Some(value()?),
+            "--once" => cli.once = true,
+            "-f" | "--format" => {
+               cli.format = match value()?.as_str() {
+                  "ansi" | "color" => FORMAT::Ansi,
+                  "plain" | "text" => FORMAT::Plain,
+                  "json" => FORMAT::Json,
+                  f => return Err(format!("unknown format '{f}', use ansi, plain or json")),
+               }
+            }
+            "--no-color" => cli.no_color = true,
+            "--fps" => cli.fps = Some(number(value()?)?),
+            "--tps" => cli.tps = Some(number(value()?)?),
+            "--rps" => cli.rps = Some(number(value()?)?),
+            "-l" | "--layout" => cli.layout = Some(value()?),
+            "--logo" => cli.logo = Some(value()?),
+            "--list-modules" => cli.list_modules = true,
+            "-V" | "--version" => cli.version = true,
+            "-h" | "--help" => cli.help = true,
+            _ => return Err(format!("unknown argument '{arg}', see --help")),
+         }
+      }
+      // katatui takes the config file name as a const, it can't be swapped at runtime
+      if cli.config.is_some() && !cli.once {
+         return Err("--config only works with --once, the tui always loads init.lua".into());
+      }
+      Ok(cli)
+   }
+
+   pub fn apply(&self, lua: &mlua::Lua) -> Result<(), String> {
+      let profile = self
+         .profile
+         .as_ref()
+         .map(|p| config_dir().join("profiles").join(format!("{p}.lua")));
+      if let Some(path) = &profile {
+         let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(e) => return Err(format!("failed to read {}: {e}", path.display())),
+         };
+         let name = path.display().to_string();
+         if let Err(e) = lua.load(src).set_name(name.as_str()).exec() {
+            return Err(format!("failed to run {name}: {e}"));
+         }
+      }
+
+      let table = match lua.globals().get::<mlua::Table>("SETTINGS") {
+         Ok(t) => t,
+         Err(e) => return Err(format!("failed to get SETTINGS in lua {e}")),
+      };
+      let set = || -> mlua::Result<()> {
+         if let Some(n) = self.fps {
+            table.set("fps", n)?;
+         }
+         if let Some(n) = self.tps {
+            table.set("tps", n)?;
+         }
+         if let Some(n) = self.rps {
+            table.set("rps", n)?;
+         }
+         if let Some(layout) = &self.layout {
+            table.set("layout", layout.as_str())?;
+         }
+         if let Some(logo) = &self.logo {
+            table.set("logo", logo.as_str())?;
+         }
+         Ok(())
+      };
+      set().map_err(|e| format!("failed to override SETTINGS {e}"))
+   }
+}
+
+// read once, so only ask after `set_cli`
+pub fn no_color() -> bool {
+   static OFF: OnceLock<bool> = OnceLock::new();
+   *OFF.get_or_init(|| cli().no_color || std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()))
+}
+
+pub fn to_json(value: &mlua::Value) -> String {
+   match value {
+      mlua::Value::Nil => "null".into(),
+      mlua::Value::Boolean(b) => b.to_string(),
+      mlua::Value::Integer(n) => n.to_string(),
+      mlua::Value::Number(n) if n.is_finite() => n.to_string(),
+      mlua::Value::Number(_) => "null".into(),
+      mlua::Value::String(s) => json_string(&s.to_string_lossy()),
+      // an empty table can't say what it is, FETCH only has empty lists
+      mlua::Value::Table(t) if t.raw_len() > 0 || t.is_empty() => {
+         let items: Vec<String> = t
+            .sequence_values::<mlua::Value>()
+            .flatten()
+            .map(|v| to_json(&v))
+            .collect();
+         format!("[{}]", items.join(","))
+      }
+      mlua::Value::Table(t) => {
+         let mut fields: Vec<(String, String)> = t
+            .pairs::<String, mlua::Value>()
+            .flatten()
+            .map(|(k, v)| (json_string(&k), to_json(&v)))
+            .collect();
+         fields.sort();
+         let fields: Vec<String> = fields
+            .into_iter()
+            .map(|(k, v)| format!("{k}:{v}"))
+            .collect();
+         format!("{{{}}}", fields.join(","))
+      }
+      _ => "null".into(),
+   }
+}
+
+fn json_string(s: &str) -> String {
+   let mut out = String::with_capacity(s.len() + 2);
+   out.push('"');
+   for c in s.chars() {
+      match c {
+         '"' => out.push_str("\\\""),
+         '\\' => out.push_str("\\\\"),
+         '\n' => out.push_str("\\n"),
+         '\r' => out.push_str("\\r"),
+         '\t' => out.push_str("\\t"),
+         c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+         c => out.push(c),
+      }
+   }
+   out.push('"');
+   out
+}
+
+pub fn ansi_line(line: &Line, color: bool) -> String {
+   let mut out = String::new();
+   for span in &line.spans {
+      let style = line.style.patch(span.style);
+      let sgr = match color {
+         true => sgr(style),
+         _ => String::new(),
+      };
+      match sgr.is_empty() {
+         true => out.push_str(&span.content),
+         _ => out.push_str(&format!("\x1b[{sgr}m{}\x1b[0m", span.content)),
+      }
+   }
+   out
+}
+
+fn sgr(style: Style) -> String {
+   let mut codes: Vec<String> = Vec::new();
+   let attrs = [
+      (Modifier::BOLD, "1"),
+      (Modifier::DIM, "2"),
+      (Modifier::ITALIC, "3"),
+      (Modifier::UNDERLINED, "4"),
+      (Modifier::SLOW_BLINK, "5"),
+      (Modifier::REVERSED, "7"),
+      (Modifier::HIDDEN, "8"),
+      (Modifier::CROSSED_OUT, "9"),
+   ];
+   for (m, code) in attrs {
+      if style.add_modifier.contains(m) {
+         codes.push(code.into());
+      }
+   }
+   if let Some(fg) = style.fg.and_then(|c| color_code(c, 30)) {
+      codes.push(fg);
+   }
+   if let Some(bg) = style.bg.and_then(|c| color_code(c, 40)) {
+      codes.push(bg);
+   }
+   codes.join(";")
+}
+
+// `base` is 30 for the foreground and 40 for the background
+fn color_code(color: Color, base: u8) -> Option<String> {
+   let named = |n: u8| match n {
+      0..8 => Some((base + n).to_string()),
+      _ => Some((base + 60 + n - 8).to_string()),
+   };
+   match color {
+      Color::Reset => None,
+      Color::Black => named(0),
+      Color::Red => named(1),
+      Color::Green => named(2),
+      Color::Yellow => named(3),
+      Color::Blue => named(4),
+      Color::Magenta => named(5),
+      Color::Cyan => named(6),
+      Color::Gray => named(7),
+      Color::DarkGray => named(8),
+      Color::LightRed => named(9),
+      Color::LightGreen => named(10),
+      Color::LightYellow => named(11),
+      Color::LightBlue => named(12),
+      Color::LightMagenta => named(13),
+      Color::LightCyan => named(14),
+      Color::White => named(15),
+      Color::Indexed(n) => Some(format!("{};5;{n}", base + 8)),
+      Color::Rgb(r, g, b) => Some(format!("{};2;{r};{g};{b}", base + 8)),
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn parse(args: &[&str]) -> Result<CLI, String> {
+      CLI::parse(args.iter().map(|a| a.to_string()))
+   }
+
+   #[test]
+   fn flag_values() {
+      let cli = parse(&["--fps", "30", "--tps=5", "-f", "json", "--once"]).unwrap();
+      assert_eq!(cli.fps, Some(30));
+      assert_eq!(cli.tps, Some(5));
+      assert_eq!(cli.format, FORMAT::Json);
+      assert!(cli.once);
+   }
+
+   #[test]
+   fn inline_value_keeps_later_equals() {
+      let cli = parse(&["--layout=a=b"]).unwrap();
+      assert_eq!(cli.layout.as_deref(), Some("a=b"));
+   }
+
+   #[test]
+   fn rejects_bad_numbers() {
+      assert!(parse(&["--fps=0"]).is_err());
+      assert!(parse(&["--rps", "-1"]).is_err());
+      assert!(parse(&["--tps", "fast"]).is_err());
+   }
+
+   #[test]
+   fn rejects_missing_values_and_unknowns() {
+      assert!(parse(&["--fps"]).is_err());
+      assert!(parse(&["-f", "xml"]).is_err());
+      assert!(parse(&["--frobnicate"]).is_err());
+      assert!(parse(&["-f=json"]).is_err());
+   }
+
+   #[test]
+   fn config_is_absolute() {
+      let cli = parse(&["-c", "my.lua", "--once"]).unwrap();
+      assert!(cli.config.unwrap().is_absolute());
+      assert!(parse(&["-c", "my.lua"]).is_err());
+   }
+
+   #[test]
+   fn json_values() {
+      let lua = mlua::Lua::new();
+      let value: mlua::Value = lua
+         .load(r#"{ name = "a\"b", list = { 1, 2.5, true }, nothing = 0/0, none = {} }"#)
+         .eval()
+         .unwrap();
+      assert_eq!(
+         to_json(&value),
+         r#"{"list":[1,2.5,true],"name":"a\"b","none":[],"nothing":null}"#
+      );
+      assert_eq!(json_string("tab\there\u{1}"), r#""tab\there\u0001""#);
+   }
+}
@@ -0,0 +1,51 @@
+use katatui::*;
+
+pub fn call_hook<R: mlua::FromLuaMulti>(
+   lua: &mlua::Lua,
+   name: &str,
+   args: impl mlua::IntoLuaMulti,
+) -> Result<Option<R>, String> {
+   match lua.globals().get::<Option<mlua::Function>>(name) {
+      Ok(Some(f)) => f
+         .call::<R>(args)
+         .map(Some)
+         .map_err(|e| format!("{name} failed: {e}")),
+      _ => Ok(None),
+   }
+}
+
+pub fn changed_fields(old: &mlua::Table, new: &mlua::Table) -> Vec<String> {
+   let mut changed: Vec<String> = new
+      .pairs::<String, mlua::Value>()
+      .flatten()
+      .filter(|(k, v)| !same(&old.get(k.as_str()).unwrap_or(mlua::Value::Nil), v))
+      .map(|(k, _)| k)
+      .collect();
+   changed.sort();
+   changed
+}
+
+pub fn same_track(old: &mlua::Value, new: &mlua::Value) -> bool {
+   let field = |t: &mlua::Table, k: &str| t.get(k).unwrap_or(mlua::Value::Nil);
+   match (old, new) {
+      (mlua::Value::Table(a), mlua::Value::Table(b)) => ["bus", "song", "artist", "album"]
+         .iter()
+         .all(|k| same(&field(a, k), &field(b, k))),
+      _ => same(old, new),
+   }
+}
+
+// compares tables by content instead of by reference
+fn same(a: &mlua::Value, b: &mlua::Value) -> bool {
+   match (a, b) {
+      (mlua::Value::Table(a), mlua::Value::Table(b)) => {
+         let len = |t: &mlua::Table| t.pairs::<mlua::Value, mlua::Value>().count();
+         len(a) == len(b)
+            && a
+               .pairs::<mlua::Value, mlua::Value>()
+               .flatten()
+               .all(|(k, v)| same(&v, &b.get(k).unwrap_or(mlua::Value::Nil)))
+      }
+      _ => a == b,
+   }
+}
@@ -0,0 +1,356 @@
+use katatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use katatui::crossterm::execute;
+use katatui::*;
+
+pub fn capture_mouse(on: bool) {
+   let mut out = std::io::stdout();
+   let _ = match on {
+      true => execute!(out, EnableMouseCapture),
+      _ => execute!(out, DisableMouseCapture),
+   };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum COMMAND {
+   Quit,
+   Reload,
+   Debug,
+   SwapLayout,
+   SwapOrder,
+   NextTheme,
+   Overlay,
+   ScrollDown,
+   ScrollUp,
+   PageDown,
+   PageUp,
+   PlayPause,
+   Next,
+   Previous,
+   VolumeUp,
+   VolumeDown,
+}
+
+impl COMMAND {
+   pub fn from_name(name: &str) -> Option<Self> {
+      let cmd = match name.to_lowercase().replace(['-', ' '], "_").as_str() {
+         "quit" | "exit" => COMMAND::Quit,
+         "reload" => COMMAND::Reload,
+         "debug" | "toggle_debug" => COMMAND::Debug,
+         "swap_layout" | "layout" => COMMAND::SwapLayout,
+         "swap_order" | "order" => COMMAND::SwapOrder,
+         "next_theme" | "theme" => COMMAND::NextTheme,
+         "overlay" | "toggle_overlay" | "perf" => COMMAND::Overlay,
+         "scroll_down" => COMMAND::ScrollDown,
+         "scroll_up" => COMMAND::ScrollUp,
+         "page_down" => COMMAND::PageDown,
+         "page_up" => COMMAND::PageUp,
+         "play_pause" | "toggle" => COMMAND::PlayPause,
+         "next" | "next_track" => COMMAND::Next,
+         "previous" | "prev" | "previous_track" => COMMAND::Previous,
+         "volume_up" => COMMAND::VolumeUp,
+         "volume_down" => COMMAND::VolumeDown,
+         _ => return None,
+      };
+      Some(cmd)
+   }
+}
+
+#[derive(Debug, Clone)]
+pub enum BINDING {
+   Command(COMMAND),
+   Call(mlua::Function),
+}
+
+// shift is folded into the character itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CHORD {
+   pub code: KeyCode,
+   pub mods: KeyModifiers,
+}
+
+impl CHORD {
+   pub fn from_event(key: &KeyEvent) -> Self {
+      CHORD::new(key.code, key.modifiers)
+   }
+
+   fn new(code: KeyCode, mods: KeyModifiers) -> Self {
+      let mods = mods
+         & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT | KeyModifiers::SUPER);
+      let (code, mods) = match code {
+         KeyCode::Char(c) if mods.contains(KeyModifiers::SHIFT) => (
+            KeyCode::Char(c.to_ascii_uppercase()),
+            mods - KeyModifiers::SHIFT,
+         ),
+         KeyCode::Tab | KeyCode::BackTab if mods.contains(KeyModifiers::SHIFT) => {
+            (KeyCode::BackTab, mods - KeyModifiers::SHIFT)
+         }
+         _ => (code, mods),
+      };
+      CHORD { code, mods }
+   }
+
+   pub fn from_name(name: &str) -> Option<Self> {
+      let (mods, key) = match name.strip_suffix('+') {
+         Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest, "+"),
+         _ => match name.rsplit_once('+') {
+            Some((mods, key)) => (mods, key),
+            None => ("", name),
+         },
+      };
+      let mut m = KeyModifiers::NONE;
+      for part in mods.split('+').filter(|p| !p.is_empty()) {
+         m |= match part.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            "super" | "cmd" | "win" => KeyModifiers::SUPER,
+            _ => return None,
+         };
+      }
+      let mut chars = key.chars();
+      let code = match (chars.next(), chars.next()) {
+         (Some(c), None) => KeyCode::Char(c),
+         _ => match key.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            "plus" => KeyCode::Char('+'),
+            f => KeyCode::F(f.strip_prefix('f')?.parse().ok()?),
+         },
+      };
+      Some(CHORD::new(code, m))
+   }
+
+   pub fn name(&self) -> String {
+      let mut name = String::new();
+      for (m, prefix) in [
+         (KeyModifiers::CONTROL, "ctrl+"),
+         (KeyModifiers::ALT, "alt+"),
+         (KeyModifiers::SUPER, "super+"),
+      ] {
+         if self.mods.contains(m) {
+            name.push_str(prefix);
+         }
+      }
+      // chars carry shift in their case already
+      if self.mods.contains(KeyModifiers::SHIFT) && !matches!(self.code, KeyCode::Char(_)) {
+         name.push_str("shift+");
+      }
+      let key = match self.code {
+         KeyCode::Char(' ') => "space".to_string(),
+         KeyCode::Char(c) => c.to_string(),
+         KeyCode::F(n) => format!("f{n}"),
+         KeyCode::Up => "up".into(),
+         KeyCode::Down => "down".into(),
+         KeyCode::Left => "left".into(),
+         KeyCode::Right => "right".into(),
+         KeyCode::PageUp => "pageup".into(),
+         KeyCode::PageDown => "pagedown".into(),
+         KeyCode::Home => "home".into(),
+         KeyCode::End => "end".into(),
+         KeyCode::Enter => "enter".into(),
+         KeyCode::Esc => "esc".into(),
+         KeyCode::Tab => "tab".into(),
+         KeyCode::BackTab => "backtab".into(),
+         KeyCode::Backspace => "backspace".into(),
+         KeyCode::Delete => "delete".into(),
+         KeyCode::Insert => "insert".into(),
+         _ => "unknown".into(),
+      };
+      name + &key
+   }
+}
+
+#[derive(Debug, Clone)]
+pub struct KEYMAP {
+   pub bindings: Vec<(CHORD, BINDING)>,
+}
+
+impl Default for KEYMAP {
+   fn default() -> Self {
+      let keys = [
+         ("q", COMMAND::Quit),
+         ("d", COMMAND::Debug),
+         ("r", COMMAND::Reload),
+         ("t", COMMAND::NextTheme),
+         ("o", COMMAND::Overlay),
+         ("up", COMMAND::SwapLayout),
+         ("down", COMMAND::SwapLayout),
+         ("left", COMMAND::SwapOrder),
+         ("right", COMMAND::SwapOrder),
+         ("j", COMMAND::ScrollDown),
+         ("k", COMMAND::ScrollUp),
+         ("pagedown", COMMAND::PageDown),
+         ("pageup", COMMAND::PageUp),
+         ("space", COMMAND::PlayPause),
+         ("n", COMMAND::Next),
+         ("p", COMMAND::Previous),
+         ("+", COMMAND::VolumeUp),
+         ("-", COMMAND::VolumeDown),
+      ];
+      let bindings = keys
+         .into_iter()
+         .filter_map(|(k, c)| Some((CHORD::from_name(k)?, BINDING::Command(c))))
+         .collect();
+      KEYMAP { bindings }
+   }
+}
+
+impl KEYMAP {
+   pub fn from_table(table: &mlua::Table) -> (Self, Vec<String>) {
+      let mut errors = Vec::new();
+      let mut named: Vec<(String, CHORD, BINDING)> = Vec::new();
+      for (name, value) in table.pairs::<String, mlua::Value>().flatten() {
+         let chord = match CHORD::from_name(&name) {
+            Some(c) => c,
+            None => {
+               errors.push(format!("unknown key '{name}' in KEYS"));
+               continue;
+            }
+         };
+         let binding = match value {
+            mlua::Value::Function(f) => BINDING::Call(f),
+            mlua::Value::String(s) => {
+               let action = s.to_string_lossy();
+               match COMMAND::from_name(&action) {
+                  Some(c) => BINDING::Command(c),
+                  None => {
+                     errors.push(format!(
+                        "unknown action '{action}' for key '{name}' in KEYS"
+                     ));
+                     continue;
+                  }
+               }
+            }
+            other => {
+               let kind = other.type_name();
+               errors.push(format!(
+                  "key '{name}' in KEYS needs an action or function, not {kind}"
+               ));
+               continue;
+            }
+         };
+         named.push((name, chord, binding));
+      }
+      // lua tables have no order, the first name alphabetically wins a conflict
+      named.sort_by(|a, b| a.0.cmp(&b.0));
+      let mut bindings: Vec<(CHORD, BINDING)> = Vec::new();
+      let mut names: Vec<&str> = Vec::new();
+      for (name, chord, binding) in &named {
+         match bindings.iter().position(|(c, _)| c == chord) {
+            Some(i) => errors.push(format!(
+               "'{name}' and '{}' are the same key in KEYS",
+               names[i]
+            )),
+            None => {
+               bindings.push((*chord, binding.clone()));
+               names.push(name);
+            }
+         }
+      }
+      (KEYMAP { bindings }, errors)
+   }
+
+   pub fn get(&self, key: &KeyEvent) -> Option<&BINDING> {
+      let chord = CHORD::from_event(key);
+      self
+         .bindings
+         .iter()
+         .find(|(c, _)| *c == chord)
+         .map(|(_, b)| b)
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn chord(code: KeyCode, mods: KeyModifiers) -> Option<CHORD> {
+      Some(CHORD { code, mods })
+   }
+
+   #[test]
+   fn plus_keys() {
+      let none = KeyModifiers::NONE;
+      assert_eq!(CHORD::from_name("+"), chord(KeyCode::Char('+'), none));
+      assert_eq!(CHORD::from_name("plus"), chord(KeyCode::Char('+'), none));
+      assert_eq!(
+         CHORD::from_name("ctrl++"),
+         chord(KeyCode::Char('+'), KeyModifiers::CONTROL)
+      );
+      assert_eq!(CHORD::from_name("ctrl+"), None);
+   }
+
+   #[test]
+   fn shift_is_folded() {
+      let none = KeyModifiers::NONE;
+      assert_eq!(CHORD::from_name("Q"), chord(KeyCode::Char('Q'), none));
+      assert_eq!(CHORD::from_name("shift+q"), CHORD::from_name("Q"));
+      assert_eq!(CHORD::from_name("shift+tab"), chord(KeyCode::BackTab, none));
+      let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+      assert_eq!(Some(CHORD::from_event(&event)), CHORD::from_name("backtab"));
+   }
+
+   #[test]
+   fn named_keys() {
+      let none = KeyModifiers::NONE;
+      assert_eq!(CHORD::from_name("space"), chord(KeyCode::Char(' '), none));
+      assert_eq!(CHORD::from_name("f5"), chord(KeyCode::F(5), none));
+      assert_eq!(
+         CHORD::from_name("Alt+Shift+Left"),
+         chord(KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT)
+      );
+      assert_eq!(CHORD::from_name("hyper+q"), None);
+      assert_eq!(CHORD::from_name("fx"), None);
+      assert_eq!(CHORD::from_name(""), None);
+   }
+
+   #[test]
+   fn names_read_back() {
+      for name in [
+         "q",
+         "Q",
+         "ctrl+r",
+         "alt+pagedown",
+         "space",
+         "f12",
+         "ctrl++",
+         "backtab",
+         "shift+f5",
+         "alt+shift+left",
+      ] {
+         let chord = CHORD::from_name(name).unwrap();
+         assert_eq!(CHORD::from_name(&chord.name()), Some(chord), "{name}");
+      }
+      let event = KeyEvent::new(KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT);
+      assert_eq!(CHORD::from_event(&event).name(), "alt+shift+left");
+   }
+
+   #[test]
+   fn keys_table_errors() {
+      let lua = mlua::Lua::new();
+      let table: mlua::Table = lua
+         .load(
+            r#"{ q = "quit", Q = "nope", ["hyper+x"] = "reload", r = 5, ["shift+q"] = "reload" }"#,
+         )
+         .eval()
+         .unwrap();
+      let (keys, errors) = KEYMAP::from_table(&table);
+      assert_eq!(keys.bindings.len(), 2);
+      assert_eq!(errors.len(), 3);
+      assert!(errors.iter().any(|e| e.contains("not integer")));
+   }
+}
@@ -0,0 +1,361 @@
+use crate::app::{ALIGN, read_style_value};
+use katatui::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SIDES(pub u16, pub u16, pub u16, pub u16);
+
+impl SIDES {
+   pub fn from_value(value: mlua::Value) -> Self {
+      match value {
+         mlua::Value::Integer(n) => {
+            let n = n.max(0) as u16;
+            SIDES(n, n, n, n)
+         }
+         mlua::Value::Table(t) => {
+            let v: Vec<u16> = t.sequence_values::<u16>().flatten().collect();
+            match v[..] {
+               [x, y] => SIDES(x, x, y, y),
+               [l, r, t, b] => SIDES(l, r, t, b),
+               _ => SIDES::default(),
+            }
+         }
+         _ => SIDES::default(),
+      }
+   }
+
+   fn width(&self) -> u16 {
+      self.0 + self.1
+   }
+
+   fn height(&self) -> u16 {
+      self.2 + self.3
+   }
+
+   pub fn inset(&self, area: Rect) -> Rect {
+      let SIDES(l, r, t, b) = *self;
+      Rect::new(
+         area.x + l.min(area.width),
+         area.y + t.min(area.height),
+         area.width.saturating_sub(l + r),
+         area.height.saturating_sub(t + b),
+      )
+   }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BREAKPOINT {
+   #[default]
+   Wide,
+   Narrow,
+   Compact,
+   Tiny,
+}
+
+impl BREAKPOINT {
+   pub fn name(&self) -> &'static str {
+      match self {
+         BREAKPOINT::Wide => "wide",
+         BREAKPOINT::Narrow => "narrow",
+         BREAKPOINT::Compact => "compact",
+         BREAKPOINT::Tiny => "tiny",
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ANCHOR {
+   pub x: ALIGN,
+   pub y: ALIGN,
+}
+
+impl Default for ANCHOR {
+   fn default() -> Self {
+      ANCHOR {
+         x: ALIGN::Center,
+         y: ALIGN::Center,
+      }
+   }
+}
+
+impl ANCHOR {
+   pub fn from_name(name: &str) -> Self {
+      let mut anchor = ANCHOR::default();
+      for part in name.to_lowercase().split(['-', '_', ' ']) {
+         match part {
+            "top" => anchor.y = ALIGN::Left,
+            "bottom" => anchor.y = ALIGN::Right,
+            "left" => anchor.x = ALIGN::Left,
+            "right" => anchor.x = ALIGN::Right,
+            _ => {}
+         }
+      }
+      anchor
+   }
+
+   // moved by `offset` without leaving `area`
+   pub fn place(&self, area: Rect, (w, h): (u16, u16), offset: (i16, i16)) -> Rect {
+      let (w, h) = (w.min(area.width), h.min(area.height));
+      let pos = |align: ALIGN, start: u16, room: u16, len: u16, off: i16| {
+         let free = room - len;
+         let at = match align {
+            ALIGN::Left => 0,
+            ALIGN::Center => free / 2,
+            ALIGN::Right => free,
+         };
+         start + (at as i32 + off as i32).clamp(0, free as i32) as u16
+      };
+      Rect::new(
+         pos(self.x, area.x, area.width, w, offset.0),
+         pos(self.y, area.y, area.height, h, offset.1),
+         w,
+         h,
+      )
+   }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FRAME {
+   pub border: Option<BorderType>,
+   pub border_style: Style,
+   pub title: Option<String>,
+   pub title_align: Alignment,
+   pub title_style: Style,
+   pub padding: SIDES,
+   pub margin: SIDES,
+}
+
+impl FRAME {
+   pub fn from_table(table: &mlua::Table, styles: Option<&mlua::Table>) -> Self {
+      let border = match table.get::<Option<String>>("border") {
+         Ok(Some(name)) => match name.as_str() {
+            "rounded" => Some(BorderType::Rounded),
+            "double" => Some(BorderType::Double),
+            "thick" => Some(BorderType::Thick),
+            "none" => None,
+            _ => Some(BorderType::Plain),
+         },
+         _ => None,
+      };
+      let style_of = |key: &str| match table.get::<mlua::Value>(key) {
+         Ok(v) => read_style_value(v, styles),
+         _ => Style::new(),
+      };
+      FRAME {
+         border,
+         border_style: style_of("border_style"),
+         title: table.get("title").unwrap_or(None),
+         title_align: match table.get::<Option<String>>("title_align") {
+            Ok(Some(a)) => match a.chars().next() {
+               Some('c') | Some('C') => Alignment::Center,
+               Some('r') | Some('R') => Alignment::Right,
+               _ => Alignment::Left,
+            },
+            _ => Alignment::Left,
+         },
+         title_style: style_of("title_style"),
+         padding: SIDES::from_value(table.get("padding").unwrap_or(mlua::Value::Nil)),
+         margin: SIDES::from_value(table.get("margin").unwrap_or(mlua::Value::Nil)),
+      }
+   }
+
+   pub fn block(&self) -> Block<'_> {
+      let mut block = Block::new();
+      if let Some(border) = self.border {
+         block = block
+            .borders(Borders::ALL)
+            .border_type(border)
+            .border_style(self.border_style);
+      }
+      if let Some(title) = &self.title {
+         block =
+            block.title(Line::styled(title.as_str(), self.title_style).alignment(self.title_align));
+      }
+      let SIDES(l, r, t, b) = self.padding;
+      block.padding(Padding::new(l, r, t, b))
+   }
+
+   pub fn extra(&self) -> (u16, u16) {
+      let border = if self.border.is_some() { 2 } else { 0 };
+      (
+         self.padding.width() + self.margin.width() + border,
+         self.padding.height() + self.margin.height() + border,
+      )
+   }
+
+   pub fn inset(&self, area: Rect) -> Rect {
+      self.margin.inset(area)
+   }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EXTENT {
+   pub len: Option<u16>,
+   pub pct: Option<u16>,
+   pub min: Option<u16>,
+   pub max: Option<u16>,
+   pub fill: Option<u16>,
+}
+
+impl EXTENT {
+   pub fn from_table(table: &mlua::Table) -> Self {
+      let mut extent = EXTENT {
+         min: table.get("min").unwrap_or(None),
+         max: table.get("max").unwrap_or(None),
+         fill: table.get("fill").unwrap_or(None),
+         ..Default::default()
+      };
+      match table.get::<mlua::Value>("size") {
+         Ok(mlua::Value::Integer(n)) => extent.len = Some(n.max(0) as u16),
+         Ok(mlua::Value::Number(n)) => extent.len = Some(n.max(0.0) as u16),
+         Ok(mlua::Value::String(s)) => {
+            let s = s.to_string_lossy();
+            match s.trim() {
+               "fill" => extent.fill = Some(extent.fill.unwrap_or(1)),
+               s => match s.strip_suffix('%') {
+                  Some(p) => extent.pct = p.trim().parse().ok(),
+                  None => extent.len = s.parse().ok(),
+               },
+            }
+         }
+         _ => {}
+      }
+      extent
+   }
+
+   pub fn stretches(&self) -> bool {
+      self.pct.is_some() || self.fill.is_some()
+   }
+
+   pub fn constraint(&self, content: u16) -> Constraint {
+      if let Some(p) = self.pct {
+         return Constraint::Percentage(p);
+      }
+      if let Some(f) = self.fill {
+         return Constraint::Fill(f);
+      }
+      Constraint::Length(self.clamp(self.len.unwrap_or(content)))
+   }
+
+   pub fn clamp(&self, len: u16) -> u16 {
+      len.max(self.min.unwrap_or(0))
+         .min(self.max.unwrap_or(u16::MAX))
+   }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BOX {
+   pub name: String,
+   pub lines: String,
+   pub frame: FRAME,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NODE {
+   Box(BOX, EXTENT),
+   Split(Direction, Vec<NODE>, EXTENT),
+}
+
+impl NODE {
+   pub fn from_table(table: &mlua::Table, styles: Option<&mlua::Table>) -> Option<Self> {
+      let extent = EXTENT::from_table(table);
+      if let Ok(name) = table.get::<String>("box") {
+         let lines = table
+            .get::<String>("lines")
+            .unwrap_or(format!("{}_LINES", name.to_uppercase()));
+         let frame = FRAME::from_table(table, styles);
+         return Some(NODE::Box(BOX { name, lines, frame }, extent));
+      }
+      let dir = match table
+         .get::<String>("dir")
+         .unwrap_or_default()
+         .chars()
+         .next()
+      {
+         Some('v') | Some('V') | Some('c') | Some('C') => Direction::Vertical,
+         _ => Direction::Horizontal,
+      };
+      let children: Vec<NODE> = table
+         .sequence_values::<mlua::Value>()
+         .flatten()
+         .filter_map(|v| match v {
+            mlua::Value::Table(t) => NODE::from_table(&t, styles),
+            mlua::Value::String(s) => {
+               let name = s.to_string_lossy();
+               let lines = format!("{}_LINES", name.to_uppercase());
+               let frame = FRAME::default();
+               Some(NODE::Box(BOX { name, lines, frame }, EXTENT::default()))
+            }
+            _ => None,
+         })
+         .collect();
+      match children.is_empty() {
+         true => None,
+         _ => Some(NODE::Split(dir, children, extent)),
+      }
+   }
+
+   pub fn extent(&self) -> &EXTENT {
+      match self {
+         NODE::Box(_, e) | NODE::Split(_, _, e) => e,
+      }
+   }
+
+   pub fn boxes(&self) -> Vec<&BOX> {
+      match self {
+         NODE::Box(b, _) => vec![b],
+         NODE::Split(_, children, _) => children.iter().flat_map(NODE::boxes).collect(),
+      }
+   }
+
+   pub fn stretches(&self) -> bool {
+      match self {
+         NODE::Box(_, e) => e.stretches(),
+         NODE::Split(_, children, e) => e.stretches() || children.iter().any(NODE::stretches),
+      }
+   }
+
+   pub fn size(&self, gap: u16, size_of: &impl Fn(&BOX) -> (u16, u16)) -> (u16, u16) {
+      match self {
+         NODE::Box(b, _) => size_of(b),
+         NODE::Split(dir, children, _) => {
+            let gaps = gap * (children.len() as u16).saturating_sub(1);
+            let sizes = children.iter().map(|c| c.size(gap, size_of));
+            match dir {
+               Direction::Horizontal => {
+                  let (w, h) = sizes.fold((0, 0), |(w, h), (cw, ch)| (w + cw, h.max(ch)));
+                  (w + gaps, h)
+               }
+               Direction::Vertical => {
+                  let (w, h) = sizes.fold((0, 0), |(w, h), (cw, ch)| (w.max(cw), h + ch));
+                  (w, h + gaps)
+               }
+            }
+         }
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn extent(src: &str) -> EXTENT {
+      let lua = mlua::Lua::new();
+      EXTENT::from_table(&lua.load(src).eval().unwrap())
+   }
+
+   #[test]
+   fn bounds_apply_to_every_size() {
+      let pct = extent(r#"{ size = "40%", min = 30, max = 50 }"#);
+      assert_eq!(pct.constraint(0), Constraint::Percentage(40));
+      assert_eq!(pct.clamp(20), 30);
+      assert_eq!(pct.clamp(60), 50);
+      assert_eq!(pct.clamp(40), 40);
+      let fill = extent(r#"{ size = "fill", max = 10 }"#);
+      assert!(fill.stretches());
+      assert_eq!(fill.clamp(25), 10);
+      let len = extent("{ size = 5, min = 8 }");
+      assert_eq!(len.constraint(0), Constraint::Length(8));
+      assert_eq!(extent("{ min = 4 }").constraint(2), Constraint::Length(4));
+   }
+}
@@ -1,5 +1,30 @@
-use crate::fetch::INFO;
+use crate::app::{
+   ACTION, ANCHOR, BINDING, BOX, BREAKPOINT, CHORD, CLI, COMMAND, EXTENT, FORMAT, FRAME, KEYMAP,
+   LINES, NODE, SIDES, Word, ansi_line, apply_theme, call_hook, capture_mouse, changed_fields, cli,
+   lines_text, lines_width, next_theme, no_color, palette_lines, read_lines,
+   read_lines_with_priority, same_track, take_style_errors, to_json, word_spots,
+};
+use crate::art::{
+   Placement, animate, effect_width, fit_color, frame_at, hide, logo_frames, parse_color,
+   resolve_path, show,
+};
+use crate::fetch::{INFO, VecMedia};
 use katatui::*;
+use std::{
+   borrow::Cow,
+   cell::{Cell, RefCell},
+   collections::HashMap,
+   fs,
+   path::PathBuf,
+   sync::{
+      Arc,
+      atomic::{AtomicU64, Ordering},
+   },
+   time::{Duration, Instant, SystemTime},
+};
+
+const ERROR_SHOWN: Duration = Duration::from_secs(10);
+const ROOM_STEP: (u16, u16) = (8, 4);
 
 #[derive(Default)]
 enum LAYOUT {
@@ -51,12 +76,261 @@ impl VARS {
    }
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SOURCE {
+   #[default]
+   Auto,
+   Off,
+   Named(String),
+   File(PathBuf),
+   Image(PathBuf, PICTURE),
+   Dir(PathBuf, PICTURE),
+   Frames(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MODE {
+   #[default]
+   Half,
+   Quadrant,
+   Braille,
+   Ascii,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DEPTH {
+   #[default]
+   TrueColor,
+   Ansi256,
+   Ansi16,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PICTURE {
+   pub mode: MODE,
+   pub depth: DEPTH,
+   pub width: Option<u16>,
+   pub height: Option<u16>,
+}
+
+impl PICTURE {
+   fn from_table(table: &mlua::Table) -> Self {
+      let mode = match table.get::<Option<String>>("mode") {
+         Ok(Some(m)) => match m.chars().nth(0).unwrap_or('h') {
+            'q' | 'Q' => MODE::Quadrant,
+            'b' | 'B' => MODE::Braille,
+            'a' | 'A' => MODE::Ascii,
+            _ => MODE::Half,
+         },
+         _ => MODE::default(),
+      };
+      let depth = match table.get::<Option<String>>("depth") {
+         Ok(Some(d)) if d.contains("256") => DEPTH::Ansi256,
+         Ok(Some(d)) if d.contains("16") => DEPTH::Ansi16,
+         _ => DEPTH::default(),
+      };
+      Self {
+         mode,
+         depth,
+         width: table.get("width").unwrap_or(None),
+         height: table.get("height").unwrap_or(None),
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SIZE {
+   #[default]
+   Large,
+   Small,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum EFFECT {
+   #[default]
+   None,
+   Cycle,
+   Wave,
+   Typewriter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ANIM {
+   pub fps: f32,
+   pub effect: EFFECT,
+   pub speed: f32,
+}
+
+impl Default for ANIM {
+   fn default() -> Self {
+      Self {
+         fps: 10.0,
+         effect: EFFECT::None,
+         speed: 1.0,
+      }
+   }
+}
+
+impl ANIM {
+   fn from_table(table: &mlua::Table) -> Self {
+      let default = ANIM::default();
+      let effect = match table.get::<Option<String>>("effect") {
+         Ok(Some(e)) => match e.chars().nth(0).unwrap_or('n') {
+            'c' | 'C' => EFFECT::Cycle,
+            'w' | 'W' => EFFECT::Wave,
+            't' | 'T' => EFFECT::Typewriter,
+            _ => EFFECT::None,
+         },
+         _ => default.effect,
+      };
+      Self {
+         fps: table.get("fps").unwrap_or(default.fps),
+         effect,
+         speed: table.get("speed").unwrap_or(default.speed),
+      }
+   }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LOGO {
+   pub source: SOURCE,
+   pub size: SIZE,
+   pub colors: Vec<Color>,
+   pub anim: ANIM,
+}
+
+impl LOGO {
+   fn from_name(name: &str) -> Self {
+      let name = name.trim().to_lowercase();
+      let (name, size) = match name.strip_suffix("_small") {
+         Some(n) => (n.to_string(), SIZE::Small),
+         None => (name, SIZE::Large),
+      };
+      let source = match name.as_str() {
+         "" | "auto" => SOURCE::Auto,
+         "none" | "off" => SOURCE::Off,
+         _ => SOURCE::Named(name),
+      };
+      Self {
+         source,
+         size,
+         colors: Vec::new(),
+         anim: ANIM::default(),
+      }
+   }
+
+   fn from_table(table: &mlua::Table) -> Self {
+      let name = table.get::<String>("name").unwrap_or("auto".into());
+      let mut logo = Self::from_name(&name);
+      if let Ok(Some(file)) = table.get::<Option<String>>("file") {
+         logo.source = SOURCE::File(resolve_path(&file));
+      }
+      if let Ok(Some(image)) = table.get::<Option<String>>("image") {
+         logo.source = SOURCE::Image(resolve_path(&image), PICTURE::from_table(table));
+      }
+      match table.get::<mlua::Value>("frames") {
+         Ok(mlua::Value::String(dir)) => {
+            let dir = resolve_path(&dir.to_string_lossy());
+            logo.source = SOURCE::Dir(dir, PICTURE::from_table(table));
+         }
+         Ok(mlua::Value::Table(frames)) => {
+            logo.source = SOURCE::Frames(frames.sequence_values::<String>().flatten().collect());
+         }
+         _ => {}
+      }
+      logo.anim = ANIM::from_table(table);
+      if let Ok(Some(size)) = table.get::<Option<String>>("size") {
+         logo.size = match size.chars().nth(0).unwrap_or('l') {
+            's' | 'S' => SIZE::Small,
+            _ => SIZE::Large,
+         };
+      }
+      if let Ok(colors) = table.get::<mlua::Table>("colors") {
+         logo.colors = colors
+            .sequence_values::<String>()
+            .flatten()
+            .filter_map(|c| parse_color(&c))
+            .map(fit_color)
+            .collect();
+      }
+      logo
+   }
+
+   // pictures without a width or height are converted to fill `room`, rounded down to
+   // steps so the info box growing by a column doesn't convert the image again
+   fn fit_to(&mut self, (w, h): (u16, u16)) {
+      if w == 0 || h == 0 {
+         return;
+      }
+      let step = |n: u16, by: u16| match n / by * by {
+         0 => n,
+         stepped => stepped,
+      };
+      if let SOURCE::Image(_, pic) | SOURCE::Dir(_, pic) = &mut self.source {
+         if pic.width.is_none() && pic.height.is_none() {
+            pic.width = Some(step(w, ROOM_STEP.0));
+            pic.height = Some(step(h, ROOM_STEP.1));
+         }
+      }
+   }
+
+   fn modified(&self) -> Option<SystemTime> {
+      match &self.source {
+         SOURCE::File(path) | SOURCE::Image(path, _) | SOURCE::Dir(path, _) => {
+            fs::metadata(path).and_then(|m| m.modified()).ok()
+         }
+         _ => None,
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GRAPHICS {
+   #[default]
+   Auto,
+   Kitty,
+   Sixel,
+   Off,
+}
+
+impl GRAPHICS {
+   fn from_name(name: &str) -> Self {
+      match name.trim().to_lowercase().as_str() {
+         "kitty" => GRAPHICS::Kitty,
+         "sixel" => GRAPHICS::Sixel,
+         "none" | "off" | "cells" => GRAPHICS::Off,
+         _ => GRAPHICS::Auto,
+      }
+   }
+}
+
+pub fn config_dir() -> PathBuf {
+   let base = match std::env::var("XDG_CONFIG_HOME") {
+      Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+      _ => PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".config"),
+   };
+   base.join(LoopFetch::APP_NAME)
+}
+
 pub struct SETTINGS {
    fps: u32,
    tps: u32,
    rps: u32,
    layout: LAYOUT,
    order: ORDER,
+   tree: Option<NODE>,
+   frames: HashMap<String, FRAME>,
+   theme: Option<String>,
+   anchor: ANCHOR,
+   margin: SIDES,
+   offset: (i16, i16),
+   gap: u16,
+   responsive: bool,
+   sticky: usize,
+   overlay: bool,
+   logo: LOGO,
+   graphics: GRAPHICS,
+   cell: (u16, u16),
    vars: VARS,
 }
 
@@ -68,34 +342,150 @@ impl Default for SETTINGS {
          rps: 3,
          layout: LAYOUT::default(),
          order: ORDER::default(),
+         tree: None,
+         frames: HashMap::new(),
+         theme: None,
+         anchor: ANCHOR::default(),
+         margin: SIDES::default(),
+         offset: (0, 0),
+         gap: 0,
+         responsive: true,
+         sticky: 0,
+         overlay: false,
+         logo: LOGO::default(),
+         graphics: GRAPHICS::default(),
+         cell: (10, 20),
          vars: VARS::default(),
       }
    }
 }
 
 impl SETTINGS {
+   fn from_table(table: &mlua::Table, styles: Option<&mlua::Table>) -> Self {
+      let default_settings = SETTINGS::default();
+      let default_layout = LAYOUT::default();
+      let default_order = ORDER::default();
+      let theme = table.get::<Option<String>>("theme").unwrap_or(None);
+      let layout = match table.get::<Option<String>>("layout") {
+         Ok(os) => match os {
+            Some(s) => {
+               let first = s.chars().nth(0).unwrap_or('h');
+               match first {
+                  'h' | 'H' => LAYOUT::Horiz,
+                  'v' | 'V' => LAYOUT::Vert,
+                  _ => default_layout,
+               }
+            }
+            _ => default_layout,
+         },
+         _ => default_layout,
+      };
+      let tree = match table.get::<mlua::Table>("layout") {
+         Ok(t) => NODE::from_table(&t, styles),
+         _ => None,
+      };
+      let frames = match table.get::<mlua::Table>("boxes") {
+         Ok(t) => t
+            .pairs::<String, mlua::Table>()
+            .flatten()
+            .map(|(name, t)| (name, FRAME::from_table(&t, styles)))
+            .collect(),
+         _ => HashMap::new(),
+      };
+      let anchor = match table.get::<Option<String>>("anchor") {
+         Ok(Some(a)) => ANCHOR::from_name(&a),
+         _ => default_settings.anchor,
+      };
+      let offset = match table.get::<mlua::Table>("offset") {
+         Ok(t) => (t.get(1).unwrap_or(0), t.get(2).unwrap_or(0)),
+         _ => default_settings.offset,
+      };
+      let order = match table.get::<mlua::Table>("order") {
+         Ok(table) => {
+            let f = table.get::<String>(1).unwrap_or("info".into());
+            match f.chars().nth(0).unwrap_or('i') {
+               'i' | 'I' => ORDER::InfoFirst,
+               'a' | 'A' => ORDER::AsciFirst,
+               _ => default_order,
+            }
+         }
+         _ => default_order,
+      };
+      let logo = match table.get::<mlua::Value>("logo") {
+         Ok(mlua::Value::String(s)) => LOGO::from_name(&s.to_string_lossy()),
+         Ok(mlua::Value::Table(t)) => LOGO::from_table(&t),
+         _ => LOGO::default(),
+      };
+      let (graphics, cell) = match table.get::<mlua::Value>("graphics") {
+         Ok(mlua::Value::String(s)) => (
+            GRAPHICS::from_name(&s.to_string_lossy()),
+            default_settings.cell,
+         ),
+         Ok(mlua::Value::Table(t)) => (
+            GRAPHICS::from_name(&t.get::<String>("protocol").unwrap_or_default()),
+            match t.get::<mlua::Table>("cell") {
+               Ok(c) => (
+                  c.get(1).unwrap_or(default_settings.cell.0),
+                  c.get(2).unwrap_or(default_settings.cell.1),
+               ),
+               _ => default_settings.cell,
+            },
+         ),
+         _ => (default_settings.graphics, default_settings.cell),
+      };
+      let default_comp = default_settings.vars.comp.clone();
+      let vars = match table.get::<mlua::Table>("vars") {
+         Ok(table) => VARS {
+            comp: table.get::<String>("comp").unwrap_or(default_comp),
+         },
+         _ => VARS { comp: default_comp },
+      };
+      SETTINGS {
+         fps: table.get("fps").unwrap_or(default_settings.fps),
+         tps: table.get("tps").unwrap_or(default_settings.tps),
+         rps: table.get("rps").unwrap_or(default_settings.rps),
+         layout,
+         order,
+         tree,
+         frames,
+         theme,
+         anchor,
+         margin: SIDES::from_value(table.get("margin").unwrap_or(mlua::Value::Nil)),
+         offset,
+         gap: table.get("gap").unwrap_or(default_settings.gap),
+         responsive: table
+            .get("responsive")
+            .unwrap_or(default_settings.responsive),
+         sticky: table.get("sticky").unwrap_or(default_settings.sticky),
+         overlay: table.get("overlay").unwrap_or(default_settings.overlay),
+         logo,
+         graphics,
+         cell,
+         vars,
+      }
+   }
+
    pub fn vars(&self) -> &VARS {
       &self.vars
    }
 }
 
-#[derive(Debug, Clone)]
-pub struct Word {
-   pub text: String,
-   pub style: Style,
-}
-
-type LINES = Vec<Vec<Word>>;
-
 #[derive(Debug, Default)]
 pub struct InfoBox {
    lines: LINES,
+   priority: Vec<Option<i32>>,
    max_len: usize,
 }
 #[derive(Debug, Default)]
 pub struct AsciBox {
    lines: LINES,
+   frames: Vec<LINES>,
+   anim: ANIM,
+   start: Option<Instant>,
    max_len: usize,
+   logo: Option<(LOGO, Option<SystemTime>)>,
+   // `None` until the first refresh checked the file
+   mtime: Option<Option<SystemTime>>,
 }
 
 pub struct LoopFetch {
@@ -103,6 +493,30 @@ pub struct LoopFetch {
    settings: SETTINGS,
    info_box: InfoBox,
    asci_box: AsciBox,
+   boxes: HashMap<String, InfoBox>,
+   theme: Option<String>,
+   breakpoint: Cell<BREAKPOINT>,
+   info_cut: Cell<Option<i32>>,
+   scroll: usize,
+   // (max scroll, page height) of the info box as last drawn
+   scroll_room: Cell<(usize, usize)>,
+   info_area: Cell<Rect>,
+   box_widths: RefCell<HashMap<String, usize>>,
+   logo_room: Cell<(u16, u16)>,
+   hits: RefCell<Vec<(Rect, ACTION)>>,
+   keys: KEYMAP,
+   keys_read: bool,
+   started: bool,
+   fetched: Option<(mlua::Table, Option<usize>)>,
+   // kept so `on_exit` can still run while tearing down
+   lua: Option<mlua::Lua>,
+   // fetchers don't report errors, this is loopfetch's own
+   last_error: Option<(String, Instant)>,
+   style_errors: Vec<String>,
+   tick_fn: Option<mlua::Function>,
+   tick_us: Arc<AtomicU64>,
+   graphics: RefCell<Option<(Placement, bool)>>,
+   asci_drawn: Cell<bool>,
 }
 
 impl App for LoopFetch {
@@ -117,105 +531,400 @@ impl App for LoopFetch {
          settings,
          info_box: InfoBox::default(),
          asci_box: AsciBox::default(),
+         boxes: HashMap::new(),
+         theme: None,
+         breakpoint: Cell::new(BREAKPOINT::default()),
+         info_cut: Cell::new(None),
+         scroll: 0,
+         scroll_room: Cell::new((0, 0)),
+         info_area: Cell::new(Rect::default()),
+         box_widths: RefCell::new(HashMap::new()),
+         logo_room: Cell::new((0, 0)),
+         hits: RefCell::new(Vec::new()),
+         keys: KEYMAP::default(),
+         keys_read: false,
+         started: false,
+         fetched: None,
+         lua: None,
+         last_error: None,
+         style_errors: Vec::new(),
+         tick_fn: None,
+         tick_us: Arc::new(AtomicU64::new(0)),
+         graphics: RefCell::new(None),
+         asci_drawn: Cell::new(false),
       };
+      app.apply_cli(&mut tui);
       app.read_cfg(&mut tui);
       app.update_tui_settings(&mut tui);
+      capture_mouse(true);
       app
    }
 
    fn logic(&mut self, mut tui: TUIMutRef, event: Option<Event>) {
-      if tui.runtime.tick() % self.settings.rps == 0 {
+      let refreshed = tui.runtime.tick() % self.settings.rps == 0;
+      if refreshed {
          self.info.refresh(&self.settings);
          tui.debug.current_log.set_event_msg("refreshed fetch!")
       };
-      if !tui.runtime.just_reloaded() {
+      if tui.runtime.just_reloaded() {
+         self.apply_cli(&mut tui);
+      } else {
          self.write_cfg(&mut tui);
       }
       self.read_cfg(&mut tui);
       self.update_tui_settings(&mut tui);
+      self.time_tick(&tui);
+      self.lua = tui.cfg.cloned();
+      self.run_hooks(&mut tui, refreshed);
       match event {
          Some(Event::Key(k)) => self.handle_key(&mut tui, k),
+         Some(Event::Mouse(m)) => self.handle_mouse(&mut tui, m),
+         Some(Event::Resize(w, h)) => {
+            self.hook::<()>(&mut tui, "on_resize", (w, h));
+         }
          _ => {}
       }
    }
 
    fn render(&self, tui: TUIRef, buf: &mut Buffer) {
-      let (a, b, a_w, b_w, a_h, b_h) = match self.settings.order {
-         ORDER::InfoFirst => (
-            0,
-            1,
-            self.info_box.max_len,
-            self.asci_box.max_len,
-            self.info_box.lines.len(),
-            self.asci_box.lines.len(),
-         ),
-         ORDER::AsciFirst => (
-            1,
-            0,
-            self.asci_box.max_len,
-            self.info_box.max_len,
-            self.asci_box.lines.len(),
-            self.info_box.lines.len(),
-         ),
+      let area = self.settings.margin.inset(buf.area);
+      self.logo_room.set(self.room_for_logo(area));
+      let (tree, breakpoint) = self.fit_tree(area);
+      self.breakpoint.set(breakpoint);
+      self.hits.borrow_mut().clear();
+      let size = match tree.stretches() {
+         true => (area.width, area.height),
+         _ => tree.size(self.settings.gap, &|b| self.box_size(b)),
       };
+      let content = self.settings.anchor.place(area, size, self.settings.offset);
 
-      let (dir, a_c, b_c, total_w, total_h) = match self.settings.layout {
-         LAYOUT::Vert => (
-            Direction::Vertical,
-            a_h,
-            b_h,
-            self.info_box.max_len.max(self.asci_box.max_len),
-            self.info_box.lines.len() + self.asci_box.lines.len(),
-         ),
-         LAYOUT::Horiz => (
-            Direction::Horizontal,
-            a_w,
-            b_w,
-            self.info_box.max_len + self.asci_box.max_len,
-            self.info_box.lines.len().max(self.asci_box.lines.len()),
-         ),
+      self.asci_drawn.set(false);
+      self.render_node(&tui, &tree, content, buf);
+      if !self.asci_drawn.get() {
+         self.hide_graphics();
+      }
+      if self.settings.overlay {
+         self.render_overlay(&tui, buf);
+      }
+   }
+}
+
+impl LoopFetch {
+   pub fn once(cli: &CLI) -> Result<String, String> {
+      let lua = mlua::Lua::new();
+      let (name, src) = match &cli.config {
+         Some(path) => match fs::read_to_string(path) {
+            Ok(src) => (path.display().to_string(), src),
+            Err(e) => return Err(format!("failed to read {}: {e}", path.display())),
+         },
+         None => {
+            let init = config_dir().join(Self::CONFIG_FILE.unwrap_or("init.lua"));
+            let src = fs::read_to_string(init);
+            (
+               "init.lua".into(),
+               src.unwrap_or_else(|_| Self::DEFAULT_CONFIG_SRC.to_string()),
+            )
+         }
       };
+      if let Err(e) = lua.load(src).set_name(name.as_str()).exec() {
+         return Err(format!("failed to run {name}: {e}"));
+      }
+      cli.apply(&lua)?;
 
-      let gap_w = (buf.area.width as usize).saturating_sub(total_w) as u16 / 2;
-      let gap_h = (buf.area.height as usize).saturating_sub(total_h) as u16 / 2;
+      let globals = lua.globals();
+      let table = globals.get::<mlua::Table>("SETTINGS").ok();
+      let theme = table
+         .as_ref()
+         .and_then(|t| t.get::<Option<String>>("theme").unwrap_or(None));
+      if let Some(name) = &theme {
+         apply_theme(&lua, name)?;
+      }
+      let styles = globals.get::<mlua::Table>("STYLES").ok();
+      let styles = styles.as_ref();
+      let settings = match &table {
+         Some(t) => SETTINGS::from_table(t, styles),
+         None => SETTINGS::default(),
+      };
 
-      let lines = Layout::new(
-         Direction::Vertical,
-         [
-            Constraint::Max(gap_h),
-            Constraint::Min(0),
-            Constraint::Max(gap_h),
-         ],
-      )
-      .split(buf.area);
-      let mid_line = Layout::new(
-         Direction::Horizontal,
-         [
-            Constraint::Max(gap_w),
-            Constraint::Min(0),
-            Constraint::Max(gap_w + 1),
-         ],
-      )
-      .split(lines[1]);
+      let info = INFO::fetch(&settings);
+      let set_globals = || -> mlua::Result<mlua::Table> {
+         let fetch = info.to_lua(&lua)?;
+         let runtime = lua.create_table()?;
+         runtime.set("fps", settings.fps)?;
+         runtime.set("tps", settings.tps)?;
+         runtime.set("frame", 0)?;
+         runtime.set("tick", 0)?;
+         runtime.set("elapsed", 0)?;
+         runtime.set("breakpoint", BREAKPOINT::default().name())?;
+         globals.set("FETCH", fetch.clone())?;
+         globals.set("TUI", runtime)?;
+         Ok(fetch)
+      };
+      let fetch = set_globals().map_err(|e| format!("failed to set FETCH and TUI in lua {e}"))?;
+      call_hook::<()>(&lua, "on_start", ())?;
+      call_hook::<()>(&lua, "tick", ())?;
+      if cli.format == FORMAT::Json {
+         return Ok(to_json(&mlua::Value::Table(fetch)) + "\n");
+      }
 
-      self.render_blank_box(&tui, lines[0], buf);
-      self.render_blank_box(&tui, mid_line[0], buf);
+      let info_lines = match globals.get::<mlua::Table>("FETCH_LINES") {
+         Ok(t) => read_lines(&t, styles),
+         _ => LINES::new(),
+      };
+      let logo_lines = match globals.get::<mlua::Table>("ASCII_LINES") {
+         Ok(t) => read_lines(&t, styles),
+         _ => logo_frames(&settings.logo, &info.os_id, &info.os_like)?
+            .into_iter()
+            .next()
+            .unwrap_or_default(),
+      };
+      for e in take_style_errors() {
+         eprintln!("loopfetch: {e}");
+      }
+      let color = cli.format == FORMAT::Ansi && !no_color();
+      let column = |lines: &LINES| -> Vec<(String, usize)> {
+         lines_text(lines, lines_width(lines))
+            .lines
+            .iter()
+            .map(|l| (ansi_line(l, color), l.width()))
+            .collect()
+      };
+      let (first, second) = match settings.order {
+         ORDER::InfoFirst => (column(&info_lines), column(&logo_lines)),
+         ORDER::AsciFirst => (column(&logo_lines), column(&info_lines)),
+      };
 
-      let content = Layout::new(
-         dir,
-         [
-            Constraint::Length(a_c as u16),
-            Constraint::Length(b_c as u16),
-         ],
+      let mut out = String::new();
+      match settings.layout {
+         LAYOUT::Horiz => {
+            let width = first.iter().map(|(_, w)| *w).max().unwrap_or(0);
+            let gap = settings.gap.max(2) as usize;
+            for i in 0..first.len().max(second.len()) {
+               let (left, w) = first.get(i).cloned().unwrap_or_default();
+               let right = second.get(i).map(|(r, _)| r.as_str()).unwrap_or("");
+               let pad = " ".repeat(width - w + gap);
+               out.push_str(format!("{left}{pad}{right}").trim_end());
+               out.push('\n');
+            }
+         }
+         LAYOUT::Vert => {
+            let gap = "\n".repeat(settings.gap.max(1) as usize);
+            let text = |col: Vec<(String, usize)>| -> Vec<String> {
+               col.into_iter().map(|(l, _)| l).collect()
+            };
+            out = text(first).join("\n") + &gap + &text(second).join("\n") + "\n";
+         }
+      }
+      Ok(out)
+   }
+
+   // with `SETTINGS.responsive` the layout flips direction when it doesn't fit,
+   // then drops the logo, then hides low priority lines
+   fn fit_tree(&self, area: Rect) -> (NODE, BREAKPOINT) {
+      self.info_cut.set(None);
+      let fits = |tree: &NODE| {
+         let (w, h) = tree.size(self.settings.gap, &|b| self.box_size(b));
+         tree.stretches() || (w <= area.width && h <= area.height)
+      };
+      let dir_of = |layout: &LAYOUT| match layout {
+         LAYOUT::Vert => Direction::Vertical,
+         LAYOUT::Horiz => Direction::Horizontal,
+      };
+      let point_of = |dir: Direction| match dir {
+         Direction::Horizontal => BREAKPOINT::Wide,
+         Direction::Vertical => BREAKPOINT::Narrow,
+      };
+
+      let tree = match &self.settings.tree {
+         Some(tree) if !self.settings.responsive || fits(tree) => {
+            return (tree.clone(), BREAKPOINT::Wide);
+         }
+         Some(tree) => tree.clone(),
+         None => {
+            let dir = dir_of(&self.settings.layout);
+            let tree = self.simple_tree(dir);
+            if !self.settings.responsive || fits(&tree) {
+               return (tree, point_of(dir));
+            }
+            let flipped = match dir {
+               Direction::Horizontal => Direction::Vertical,
+               Direction::Vertical => Direction::Horizontal,
+            };
+            let tree = self.simple_tree(flipped);
+            if fits(&tree) {
+               return (tree, point_of(flipped));
+            }
+            let info = self.builtin_box("info");
+            if fits(&info) {
+               return (info, BREAKPOINT::Compact);
+            }
+            info
+         }
+      };
+
+      let mut cuts: Vec<i32> = self.info_box.priority.iter().flatten().copied().collect();
+      cuts.sort();
+      cuts.dedup();
+      for cut in cuts {
+         self.info_cut.set(Some(cut));
+         if fits(&tree) {
+            break;
+         }
+      }
+      (tree, BREAKPOINT::Tiny)
+   }
+
+   fn room_for_logo(&self, area: Rect) -> (u16, u16) {
+      let (iw, ih) = self.builtin_box("info").size(0, &|b| self.box_size(b));
+      let ascii = BOX {
+         name: "ascii".into(),
+         lines: String::new(),
+         frame: FRAME::default(),
+      };
+      let (fw, fh) = self.frame_of(&ascii).extra();
+      let gap = self.settings.gap;
+      let (w, h) = (area.width, area.height);
+      match self.settings.layout {
+         LAYOUT::Horiz => (w.saturating_sub(iw + gap + fw), h.saturating_sub(fh)),
+         LAYOUT::Vert => (w.saturating_sub(fw), h.saturating_sub(ih + gap + fh)),
+      }
+   }
+
+   fn builtin_box(&self, name: &str) -> NODE {
+      let b = BOX {
+         name: name.into(),
+         lines: String::new(),
+         frame: FRAME::default(),
+      };
+      NODE::Box(b, EXTENT::default())
+   }
+
+   fn info_lines(&self) -> Cow<'_, LINES> {
+      let cut = match self.info_cut.get() {
+         Some(cut) => cut,
+         None => return Cow::Borrowed(&self.info_box.lines),
+      };
+      let lines = self.info_box.lines.iter().enumerate();
+      Cow::Owned(
+         lines
+            .filter(|(i, _)| !self.info_hidden(*i, cut))
+            .map(|(_, words)| words.clone())
+            .collect(),
       )
-      .split(mid_line[1]);
+   }
 
-      self.render_info_box(&tui, content[a], buf);
-      self.render_asci_box(&tui, content[b], buf);
+   fn info_len(&self) -> usize {
+      match self.info_cut.get() {
+         Some(cut) => (0..self.info_box.lines.len())
+            .filter(|i| !self.info_hidden(*i, cut))
+            .count(),
+         None => self.info_box.lines.len(),
+      }
+   }
+
+   fn info_hidden(&self, i: usize, cut: i32) -> bool {
+      matches!(self.info_box.priority.get(i), Some(Some(p)) if *p <= cut)
+   }
+
+   fn simple_tree(&self, dir: Direction) -> NODE {
+      let children = match self.settings.order {
+         ORDER::InfoFirst => vec![self.builtin_box("info"), self.builtin_box("ascii")],
+         ORDER::AsciFirst => vec![self.builtin_box("ascii"), self.builtin_box("info")],
+      };
+      NODE::Split(dir, children, EXTENT::default())
+   }
+
+   // `SETTINGS.boxes[name]` wins over the frame keys of the box itself
+   fn frame_of<'a>(&'a self, b: &'a BOX) -> &'a FRAME {
+      self.settings.frames.get(&b.name).unwrap_or(&b.frame)
+   }
+
+   fn box_size(&self, b: &BOX) -> (u16, u16) {
+      let (w, h) = match b.name.as_str() {
+         // a scrolling info box needs a column for its scrollbar
+         "info" => {
+            let bar = (self.scroll_room.get().0 > 0) as usize;
+            (self.info_box.max_len + bar, self.info_len())
+         }
+         "ascii" => (self.asci_box.max_len, self.asci_box.lines.len()),
+         name => match self.boxes.get(name) {
+            Some(bx) => (bx.max_len, bx.lines.len()),
+            None => (0, 0),
+         },
+      };
+      let (extra_w, extra_h) = self.frame_of(b).extra();
+      (w as u16 + extra_w, h as u16 + extra_h)
+   }
+
+   fn render_node(&self, tui: &TUIRef, node: &NODE, area: Rect, buf: &mut Buffer) {
+      match node {
+         NODE::Box(b, _) => {
+            let frame = self.frame_of(b);
+            let area = frame.inset(area);
+            let block = frame.block();
+            let inner = block.inner(area);
+            block.render(area, buf);
+            self
+               .box_widths
+               .borrow_mut()
+               .insert(b.name.clone(), inner.width as usize);
+            match b.name.as_str() {
+               "info" => self.render_info_box(tui, inner, buf),
+               "ascii" => self.render_asci_box(tui, inner, buf),
+               name => match self.boxes.get(name) {
+                  Some(bx) => self.render_lines_box(bx, inner, buf),
+                  None => self.render_blank_box(tui, inner, buf),
+               },
+            }
+         }
+         NODE::Split(dir, children, _) => {
+            let mut constraints: Vec<Constraint> = children
+               .iter()
+               .map(|c| {
+                  let (w, h) = c.size(self.settings.gap, &|b| self.box_size(b));
+                  let content = match dir {
+                     Direction::Horizontal => w,
+                     Direction::Vertical => h,
+                  };
+                  c.extent().constraint(content)
+               })
+               .collect();
+            let split = |constraints: &[Constraint]| {
+               Layout::new(*dir, constraints.to_vec())
+                  .spacing(self.settings.gap)
+                  .split(area)
+            };
+            let mut areas = split(&constraints);
+            // min/max on "40%" or "fill" hold the length the split gave them,
+            // each pass pins at least one child so this ends
+            for _ in 0..children.len() {
+               let mut pinned = false;
+               for (i, child) in children.iter().enumerate() {
+                  let extent = child.extent();
+                  if !extent.stretches() || matches!(constraints[i], Constraint::Length(_)) {
+                     continue;
+                  }
+                  let len = match dir {
+                     Direction::Horizontal => areas[i].width,
+                     Direction::Vertical => areas[i].height,
+                  };
+                  if extent.clamp(len) != len {
+                     constraints[i] = Constraint::Length(extent.clamp(len));
+                     pinned = true;
+                  }
+               }
+               if !pinned {
+                  break;
+               }
+               areas = split(&constraints);
+            }
+            for (child, area) in children.iter().zip(areas.iter()) {
+               self.render_node(tui, child, *area, buf);
+            }
+         }
+      }
    }
-}
 
-impl LoopFetch {
    fn write_cfg(&mut self, tui: &mut TUIMutRef) -> AppOutput<()> {
       let lua = match tui.cfg {
          None => return AppOutput::void(),
@@ -228,6 +937,8 @@ impl LoopFetch {
             let _ = table.set("fps", self.settings.fps);
             let _ = table.set("tps", self.settings.tps);
             let _ = table.set("rps", self.settings.rps);
+            let _ = table.set("theme", self.settings.theme.clone());
+            let _ = table.set("overlay", self.settings.overlay);
 
             let order = match table.get::<mlua::Table>("order") {
                Err(e) => return app_err!("failed to get SETTINGS.order in lua {e}"),
@@ -243,11 +954,13 @@ impl LoopFetch {
                   let _ = order.set(2, "info");
                }
             };
-            match self.settings.layout {
-               LAYOUT::Vert => {
+            // a layout tree is left as the config wrote it
+            match (&self.settings.tree, &self.settings.layout) {
+               (Some(_), _) => {}
+               (None, LAYOUT::Vert) => {
                   let _ = table.set("layout", "vertical");
                }
-               LAYOUT::Horiz => {
+               (None, LAYOUT::Horiz) => {
                   let _ = table.set("layout", "horizontal");
                }
             }
@@ -263,6 +976,7 @@ impl LoopFetch {
          Err(e) => return app_err!("failed to convert TUI to lua {e}"),
          Ok(i) => i,
       };
+      let _ = loop_lua.set("breakpoint", self.breakpoint.get().name());
 
       match lua.globals().set("FETCH", info_lua) {
          Err(e) => return app_err!("failed to set FETCH in lua {}", e),
@@ -277,10 +991,6 @@ impl LoopFetch {
    }
 
    fn read_cfg(&mut self, tui: &mut TUIMutRef) {
-      let default_settings = SETTINGS::default();
-      let default_layout = LAYOUT::default();
-      let default_order = ORDER::default();
-      let default_style = Style::new();
       let default_lines = LINES::new();
 
       let lua = match tui.cfg {
@@ -290,111 +1000,141 @@ impl LoopFetch {
 
       let globals = lua.globals();
 
-      let settings = match globals.get::<mlua::Table>("SETTINGS") {
-         Ok(table) => {
-            let layout = match table.get::<Option<String>>("layout") {
-               Ok(os) => match os {
-                  Some(s) => {
-                     let first = s.chars().nth(0).unwrap_or('h');
-                     match first {
-                        'h' | 'H' => LAYOUT::Horiz,
-                        'v' | 'V' => LAYOUT::Vert,
-                        _ => default_layout,
-                     }
-                  }
-                  _ => default_layout,
-               },
-               _ => default_layout,
-            };
-            let order = match table.get::<mlua::Table>("order") {
-               Ok(table) => {
-                  let f = table.get::<String>(1).unwrap_or("info".into());
-                  match f.chars().nth(0).unwrap_or('i') {
-                     'i' | 'I' => ORDER::InfoFirst,
-                     'a' | 'A' => ORDER::AsciFirst,
-                     _ => default_order,
-                  }
-               }
-               _ => default_order,
-            };
-            let default_comp = default_settings.vars.comp.clone();
-            let vars = match table.get::<mlua::Table>("vars") {
-               Ok(table) => VARS {
-                  comp: table.get::<String>("comp").unwrap_or(default_comp),
-               },
-               _ => VARS { comp: default_comp },
-            };
-            SETTINGS {
-               fps: table.get("fps").unwrap_or(default_settings.fps),
-               tps: table.get("tps").unwrap_or(default_settings.tps),
-               rps: table.get("rps").unwrap_or(default_settings.rps),
-               layout,
-               order,
-               vars,
+      // a reload runs init.lua again, which puts its own COLORS and STYLES back
+      if tui.runtime.just_reloaded() {
+         self.theme = None;
+      }
+      let theme = match globals.get::<mlua::Table>("SETTINGS") {
+         Ok(table) => table.get::<Option<String>>("theme").unwrap_or(None),
+         _ => None,
+      };
+      if theme != self.theme {
+         if let Some(name) = &theme {
+            if let Err(e) = apply_theme(lua, name) {
+               self.report(tui, &e);
             }
          }
-         _ => default_settings,
-      };
-
-      let lines: Vec<Vec<Word>> = match globals.get::<mlua::Table>("FETCH_LINES") {
-         Ok(lines_table) => {
-            let mut result = Vec::new();
-
-            for line_res in lines_table.sequence_values::<mlua::Table>() {
-               let line_tbl = match line_res {
-                  Ok(tbl) => tbl,
-                  _ => continue,
-               };
-               let mut words = Vec::new();
-
-               for span_res in line_tbl.sequence_values::<mlua::Table>() {
-                  let span_tbl = match span_res {
-                     Ok(tbl) => tbl,
-                     _ => continue,
-                  };
-
-                  let text: String = span_tbl.get("text").unwrap_or_default();
-                  let style_tbl: mlua::Table = match span_tbl.get("style") {
-                     Ok(tbl) => tbl,
-                     _ => continue,
-                  };
-                  let mut style = default_style;
-
-                  if let Ok(Some(fg_hex)) = style_tbl.get::<Option<String>>("fg") {
-                     style = style.fg(hex_to_rgb(&fg_hex));
-                  }
+         self.theme = theme;
+      }
+      let styles = globals.get::<mlua::Table>("STYLES").ok();
+      let styles = styles.as_ref();
 
-                  if let Ok(Some(bg_hex)) = style_tbl.get::<Option<String>>("bg") {
-                     style = style.bg(hex_to_rgb(&bg_hex));
-                  }
+      let settings = match globals.get::<mlua::Table>("SETTINGS") {
+         Ok(table) => SETTINGS::from_table(&table, styles),
+         _ => SETTINGS::default(),
+      };
 
-                  if let Ok(true) = style_tbl.get("bold") {
-                     style = style.add_modifier(Modifier::BOLD);
-                  }
+      let (lines, priority, max_len) = match globals.get::<mlua::Table>("FETCH_LINES") {
+         Ok(lines_table) => read_lines_with_priority(&lines_table, styles, self.wrap_at("info")),
+         _ => {
+            let max_len = lines_width(&default_lines);
+            (default_lines, Vec::new(), max_len)
+         }
+      };
+      let asci_lines = match globals.get::<mlua::Table>("ASCII_LINES") {
+         Ok(lines_table) => Some(read_lines(&lines_table, styles)),
+         _ => None,
+      };
+      // KEYS only changes when init.lua runs
+      if !self.keys_read || tui.runtime.just_reloaded() {
+         let (keys, key_errors) = match globals.get::<mlua::Table>("KEYS") {
+            Ok(t) => KEYMAP::from_table(&t),
+            _ => (KEYMAP::default(), Vec::new()),
+         };
+         for e in &key_errors {
+            self.report(tui, e);
+         }
+         self.keys = keys;
+         self.keys_read = true;
+      }
 
-                  if let Ok(true) = style_tbl.get("italic") {
-                     style = style.add_modifier(Modifier::ITALIC);
-                  }
+      self.settings = settings;
+      self.info_box.max_len = max_len;
+      self.info_box.lines = lines;
+      self.info_box.priority = priority;
 
-                  words.push(Word { text, style });
+      let boxes = match &self.settings.tree {
+         Some(tree) => tree.boxes(),
+         None => Vec::new(),
+      };
+      self.boxes = boxes
+         .into_iter()
+         .filter(|b| b.name != "info" && b.name != "ascii")
+         .map(|b| {
+            let wrap_at = self.wrap_at(&b.name);
+            let (lines, _, max_len) = match globals.get::<mlua::Table>(b.lines.as_str()) {
+               Ok(lines_table) => read_lines_with_priority(&lines_table, styles, wrap_at),
+               _ if b.name == "palette" => {
+                  let lines = palette_lines();
+                  let max_len = lines_width(&lines);
+                  (lines, Vec::new(), max_len)
                }
+               _ => (LINES::new(), Vec::new(), 0),
+            };
+            let priority = Vec::new();
+            (
+               b.name.clone(),
+               InfoBox {
+                  lines,
+                  priority,
+                  max_len,
+               },
+            )
+         })
+         .collect();
 
-               result.push(words);
-            }
+      // styles are read every tick, only new problems get reported
+      let style_errors = take_style_errors();
+      let new: Vec<&String> = style_errors
+         .iter()
+         .filter(|e| !self.style_errors.contains(e))
+         .collect();
+      for e in new {
+         self.report(tui, e);
+      }
+      self.style_errors = style_errors;
 
-            result
+      if tui.runtime.just_reloaded() {
+         self.asci_box.logo = None;
+         self.asci_box.mtime = None;
+      }
+      if let Some(lines) = asci_lines {
+         self.asci_box.max_len = lines_width(&lines);
+         self.asci_box.lines = lines;
+         self.asci_box.frames = Vec::new();
+         self.asci_box.anim = ANIM::default();
+         self.asci_box.logo = None;
+      } else {
+         let mut logo = self.settings.logo.clone();
+         logo.fit_to(self.logo_room.get());
+         // the file is only looked at again on refreshes
+         let due = tui.runtime.tick() % self.settings.rps == 0;
+         let mtime = match self.asci_box.mtime {
+            Some(mtime) if !due => mtime,
+            _ => logo.modified(),
+         };
+         self.asci_box.mtime = Some(mtime);
+         let key = (logo, mtime);
+         if self.asci_box.logo.as_ref() != Some(&key) {
+            let logo = &key.0;
+            let (os_id, os_like) = (&self.info.os_id, &self.info.os_like);
+            let frames = match logo_frames(logo, os_id, os_like) {
+               Ok(frames) => frames,
+               Err(e) => {
+                  tui.debug.current_log.set_event_msg(&e);
+                  self.last_error = Some((e, Instant::now()));
+                  logo_frames(&LOGO::default(), os_id, os_like).unwrap_or_default()
+               }
+            };
+            self.asci_box.max_len =
+               frames.iter().map(lines_width).max().unwrap_or(0) + effect_width(&logo.anim);
+            self.asci_box.lines = frames.first().cloned().unwrap_or_default();
+            self.asci_box.frames = frames;
+            self.asci_box.anim = logo.anim;
+            self.asci_box.start = Some(Instant::now());
+            self.asci_box.logo = Some(key);
          }
-         _ => default_lines,
-      };
-      self.settings = settings;
-      self.info_box.max_len = lines
-         .iter()
-         .map(|words| words.iter().map(|w| w.text.len()).sum::<usize>())
-         .max()
-         .unwrap_or(0);
-      self.info_box.lines = lines;
-      self.asci_box.max_len = 30;
-      self.asci_box.lines = vec![Vec::<Word>::default(); 10];
+      }
    }
 
    fn update_tui_settings(&self, tui: &mut TUIMutRef) {
@@ -402,55 +1142,257 @@ impl LoopFetch {
       tui.runtime.set_tps(self.settings.tps);
    }
 
+   // the width `wrap` lines without a `max` wrap at, as the box was last drawn
+   fn wrap_at(&self, name: &str) -> Option<usize> {
+      self
+         .box_widths
+         .borrow()
+         .get(name)
+         .copied()
+         .filter(|w| *w > 0)
+   }
+
    fn render_info_box(&self, _tui: &TUIRef, layout: Rect, buf: &mut Buffer) {
-      let mut text = Vec::<Line>::new();
-      for line in &self.info_box.lines {
-         let mut spans = Vec::<Span>::new();
-         for word in line {
-            spans.push(Span::styled(&word.text, word.style));
+      self.info_area.set(layout);
+      let lines = self.info_lines();
+      let height = layout.height as usize;
+      if height == 0 {
+         self.scroll_room.set((0, 0));
+         return;
+      }
+      if lines.len() <= height {
+         self.scroll_room.set((0, height));
+         self.record_hits(&lines, layout);
+         let block = Block::new();
+         Paragraph::new(lines_text(&lines, layout.width as usize))
+            .block(block)
+            .render(layout, buf);
+         return;
+      }
+
+      let sticky = self.settings.sticky.min(height.saturating_sub(1));
+      let page = height - sticky;
+      let max = lines.len() - sticky - page;
+      let scroll = self.scroll.min(max);
+      self.scroll_room.set((max, page));
+      let text_width = layout.width.saturating_sub(1) as usize;
+      self
+         .box_widths
+         .borrow_mut()
+         .insert("info".into(), text_width);
+
+      let visible: LINES = lines[..sticky]
+         .iter()
+         .chain(&lines[sticky + scroll..sticky + scroll + page])
+         .cloned()
+         .collect();
+      let text_area = Rect::new(
+         layout.x,
+         layout.y,
+         layout.width.saturating_sub(1),
+         layout.height,
+      );
+      self.record_hits(&visible, text_area);
+      Paragraph::new(lines_text(&visible, text_area.width as usize))
+         .block(Block::new())
+         .render(text_area, buf);
+
+      let thumb = (page * page / (page + max)).max(1);
+      let start = scroll * page.saturating_sub(thumb) / max;
+      for row in 0..page {
+         let symbol = match row >= start && row < start + thumb {
+            true => "┃",
+            _ => "│",
+         };
+         let y = layout.y + (sticky + row) as u16;
+         if let Some(cell) = buf.cell_mut((layout.right().saturating_sub(1), y)) {
+            cell.set_symbol(symbol);
          }
-         text.push(Line::from(spans));
       }
-      let block = Block::new();
-      Paragraph::new(Text::from(text))
-         .block(block)
+   }
+
+   fn record_hits(&self, lines: &LINES, area: Rect) {
+      let mut hits = self.hits.borrow_mut();
+      for (y, line) in lines.iter().enumerate().take(area.height as usize) {
+         for (x, w, word) in word_spots(line, area.width as usize) {
+            if let Word::Act(_, action) = word {
+               let rect = Rect::new(area.x + x as u16, area.y + y as u16, w as u16, 1);
+               hits.push((rect.intersection(area), action.clone()));
+            }
+         }
+      }
+   }
+
+   fn scroll_by(&mut self, lines: isize) {
+      let (max, _) = self.scroll_room.get();
+      self.scroll = self.scroll.min(max).saturating_add_signed(lines).min(max);
+   }
+
+   fn render_lines_box(&self, bx: &InfoBox, layout: Rect, buf: &mut Buffer) {
+      self.record_hits(&bx.lines, layout);
+      Paragraph::new(lines_text(&bx.lines, layout.width as usize))
+         .block(Block::new())
          .render(layout, buf);
    }
 
-   fn render_asci_box(&self, tui: &TUIRef, layout: Rect, buf: &mut Buffer) {
+   fn render_asci_box(&self, _tui: &TUIRef, layout: Rect, buf: &mut Buffer) {
+      self.asci_drawn.set(true);
+      if self.render_graphics(layout, buf) {
+         return;
+      }
+      if !self.asci_box.lines.is_empty() {
+         let asci = &self.asci_box;
+         let t = asci.start.map(|s| s.elapsed().as_secs_f32()).unwrap_or(0.0);
+         let lines = frame_at(&asci.frames, &asci.anim, t).unwrap_or(&asci.lines);
+         let text = match asci.anim.effect {
+            EFFECT::None => lines_text(lines, layout.width as usize),
+            _ => animate(lines, &asci.anim, t),
+         };
+         Paragraph::new(text).block(Block::new()).render(layout, buf);
+      }
+   }
+
+   fn render_overlay(&self, tui: &TUIRef, buf: &mut Buffer) {
       let runtime = tui.runtime;
-      let fps_line = Line::from(format!(
-         "fps: {:06.2} {} [{:06}/{:06} ms] ({:02})",
-         runtime.fps(),
-         runtime.target_fps(),
-         runtime.frame_ms(),
-         runtime.budget(),
-         runtime.frame() % runtime.target_fps(),
-      ));
-      let tps_line = Line::from(format!(
-         "tps: {:06.2} {} [{:06}/{:06} ms] ({:02})",
-         runtime.tps(),
-         runtime.target_tps(),
-         runtime.tick_ms(),
-         runtime.budget(),
-         runtime.tick() % runtime.target_tps(),
-      ));
-      let reloaded = if tui.runtime.is_reloading() {
-         "reloaded..."
-      } else {
-         ""
+      let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+      let mut lines = vec![
+         Line::from(format!(
+            "fps {:6.2}/{} frame {} ms budget {} ms",
+            runtime.fps(),
+            runtime.target_fps(),
+            runtime.frame_ms(),
+            runtime.budget(),
+         )),
+         Line::from(format!(
+            "tps {:6.2}/{} tick {} ms rps {}",
+            runtime.tps(),
+            runtime.target_tps(),
+            runtime.tick_ms(),
+            self.settings.rps,
+         )),
+         Line::from(format!(
+            "lua tick() {:.2} ms",
+            self.tick_us.load(Ordering::Relaxed) as f64 / 1000.0
+         )),
+      ];
+      lines.extend(
+         self
+            .info
+            .timings
+            .iter()
+            .map(|(name, d)| Line::from(format!("{name:>8} {:7.2} ms", ms(*d)))),
+      );
+      lines.push(Line::from(format!(
+         "self cpu {:.1}% rss {:.1} MiB",
+         self.info.own_cpu,
+         self.info.own_rss as f64 / (1024.0 * 1024.0),
+      )));
+      match &self.last_error {
+         Some((e, at)) if at.elapsed() < ERROR_SHOWN => {
+            let age = at.elapsed().as_secs();
+            let line = format!("error {age}s ago: {e}");
+            lines.push(Line::styled(line, Style::new().fg(fit_color(Color::Red))));
+         }
+         _ => {}
+      }
+
+      let area = buf.area;
+      let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2).min(area.width);
+      let height = (lines.len() as u16 + 2).min(area.height);
+      let rect = Rect::new(area.x + area.width - width, area.y, width, height);
+      Clear.render(rect, buf);
+      Paragraph::new(Text::from(lines))
+         .block(Block::new().borders(Borders::ALL).title(" perf "))
+         .render(rect, buf);
+   }
+
+   fn time_tick(&mut self, tui: &TUIMutRef) {
+      let lua = match tui.cfg {
+         None => return,
+         Some(l) => l,
+      };
+      let tick = match lua.globals().get::<mlua::Function>("tick") {
+         Ok(f) => f,
+         _ => return,
       };
+      if self.tick_fn.as_ref() == Some(&tick) {
+         return;
+      }
+      let spent = self.tick_us.clone();
+      let wrapper = lua.create_function(move |_, args: mlua::MultiValue| {
+         let start = Instant::now();
+         let out = tick.call::<mlua::MultiValue>(args);
+         spent.store(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+         out
+      });
+      if let Ok(w) = wrapper {
+         if lua.globals().set("tick", w.clone()).is_ok() {
+            self.tick_fn = Some(w);
+         }
+      }
+   }
 
-      let rps_line = Line::from(format!("rps: {:02.2} {reloaded}", self.settings.rps));
-      let area_line = Line::from(format!(
-         "area: {} x {}  ({})",
-         layout.width, layout.height, self.info_box.max_len,
-      ));
+   // the image bypasses the backend, `emit` says why that's safe mid-draw
+   fn render_graphics(&self, layout: Rect, buf: &mut Buffer) -> bool {
+      let mut shown = self.graphics.borrow_mut();
+      let placement = self.placement(layout, buf.area);
+      if shown.as_ref().map(|(p, _)| p) != placement.as_ref() {
+         if let Some((old, true)) = shown.take() {
+            hide(&old);
+         }
+         *shown = placement.map(|p| {
+            let ok = show(&p, self.settings.cell).is_ok();
+            (p, ok)
+         });
+      }
+      match &*shown {
+         Some((p, true)) => {
+            for y in p.area.top()..p.area.bottom() {
+               for x in p.area.left()..p.area.right() {
+                  if let Some(cell) = buf.cell_mut((x, y)) {
+                     cell.set_skip(true);
+                  }
+               }
+            }
+            true
+         }
+         _ => false,
+      }
+   }
 
-      let block = Block::new();
-      Paragraph::new(Text::from(vec![fps_line, tps_line, rps_line, area_line]))
-         .block(block)
-         .render(layout, buf);
+   // nothing else draws over a kitty image, it has to be deleted
+   fn hide_graphics(&self) {
+      if let Some((old, true)) = self.graphics.borrow_mut().take() {
+         hide(&old);
+      }
+   }
+
+   fn placement(&self, layout: Rect, screen: Rect) -> Option<Placement> {
+      let protocol = self.settings.graphics.protocol()?;
+      let (logo, modified) = self.asci_box.logo.as_ref()?;
+      let path = match &logo.source {
+         SOURCE::Image(path, _) => path,
+         _ => return None,
+      };
+      // animations keep playing as cells, graphics only cover still images
+      if self.asci_box.frames.len() > 1 || self.asci_box.anim.effect != EFFECT::None {
+         return None;
+      }
+      let (cols, rows) = (
+         self.asci_box.max_len as u16,
+         self.asci_box.lines.len() as u16,
+      );
+      // a clipped image can't be cut like text can, cells degrade better
+      if cols > layout.width || rows > layout.height {
+         return None;
+      }
+      Some(Placement {
+         protocol,
+         path: path.clone(),
+         modified: *modified,
+         area: Rect::new(layout.x, layout.y, cols, rows),
+         screen,
+      })
    }
 
    fn render_blank_box(&self, tui: &TUIRef, layout: Rect, buf: &mut Buffer) {
@@ -459,17 +1401,207 @@ impl LoopFetch {
       p.render(layout, buf);
    }
 
+   fn apply_cli(&mut self, tui: &mut TUIMutRef) {
+      let lua = match tui.cfg {
+         None => return,
+         Some(l) => l,
+      };
+      if let Err(e) = cli().apply(lua) {
+         self.report(tui, &e);
+      }
+   }
+
+   fn report(&mut self, tui: &mut TUIMutRef, e: &str) {
+      tui.debug.current_log.set_event_msg(e);
+      self.last_error = Some((e.to_string(), Instant::now()));
+   }
+
+   fn hook<R: mlua::FromLuaMulti>(
+      &mut self,
+      tui: &mut TUIMutRef,
+      name: &str,
+      args: impl mlua::IntoLuaMulti,
+   ) -> Option<R> {
+      let lua = tui.cfg?;
+      match call_hook(lua, name, args) {
+         Ok(r) => r,
+         Err(e) => {
+            self.report(tui, &e);
+            None
+         }
+      }
+   }
+
+   fn run_hooks(&mut self, tui: &mut TUIMutRef, refreshed: bool) {
+      let lua = match tui.cfg {
+         None => return,
+         Some(l) => l,
+      };
+      if tui.runtime.just_reloaded() {
+         self.fetched = None;
+         self.hook::<()>(tui, "on_reload", ());
+      } else if !self.started {
+         self.hook::<()>(tui, "on_start", ());
+      }
+      self.started = true;
+
+      let fetch = match lua.globals().get::<mlua::Table>("FETCH") {
+         Ok(t) if refreshed => t,
+         _ => return,
+      };
+      let active = self.info.media.active();
+      let (old, old_active) = match self.fetched.replace((fetch.clone(), active)) {
+         Some(old) => old,
+         None => return,
+      };
+      let changed = changed_fields(&old, &fetch);
+      if !changed.is_empty() {
+         self.hook::<()>(tui, "on_refresh", changed);
+      }
+      let media = |t: &mlua::Table, i: Option<usize>| match (t.get::<mlua::Table>("media"), i) {
+         (Ok(m), Some(i)) => m.get::<mlua::Value>(i + 1).unwrap_or(mlua::Value::Nil),
+         _ => mlua::Value::Nil,
+      };
+      let (old, new) = (media(&old, old_active), media(&fetch, active));
+      if !same_track(&old, &new) {
+         self.hook::<()>(tui, "on_media_change", (old, new));
+      }
+   }
+
    fn handle_key(&mut self, tui: &mut TUIMutRef, key_event: KeyEvent) {
-      let kind = key_event.kind;
-      if kind == KeyEventKind::Press {
-         match key_event.code {
-            KeyCode::Char('q') => tui.runtime.request_exit(),
-            KeyCode::Char('d') => tui.runtime.toggle_debug(),
-            KeyCode::Char('r') => tui.runtime.request_reload(),
-            KeyCode::Up | KeyCode::Down => self.settings.layout.swap(),
-            KeyCode::Left | KeyCode::Right => self.settings.order.swap(),
-            _ => {}
+      if key_event.kind != KeyEventKind::Press {
+         return;
+      }
+      // on_key returning true swallows the key
+      let name = CHORD::from_event(&key_event).name();
+      if let Some(Some(true)) = self.hook::<Option<bool>>(tui, "on_key", name) {
+         return;
+      }
+      let result = match self.keys.get(&key_event).cloned() {
+         Some(BINDING::Command(cmd)) => self.run_command(tui, cmd),
+         Some(BINDING::Call(f)) => f
+            .call::<()>(())
+            .map_err(|e| format!("key binding failed: {e}")),
+         None => Ok(()),
+      };
+      if let Err(e) = result {
+         self.report(tui, &e);
+      }
+   }
+
+   fn run_command(&mut self, tui: &mut TUIMutRef, cmd: COMMAND) -> Result<(), String> {
+      let page = self.scroll_room.get().1 as isize;
+      match cmd {
+         COMMAND::Quit => tui.runtime.request_exit(),
+         COMMAND::Debug => tui.runtime.toggle_debug(),
+         COMMAND::Reload => tui.runtime.request_reload(),
+         COMMAND::NextTheme => self.settings.theme = next_theme(self.settings.theme.as_deref()),
+         COMMAND::Overlay => self.settings.overlay = !self.settings.overlay,
+         COMMAND::SwapLayout => self.settings.layout.swap(),
+         COMMAND::SwapOrder => self.settings.order.swap(),
+         COMMAND::ScrollDown => self.scroll_by(1),
+         COMMAND::ScrollUp => self.scroll_by(-1),
+         COMMAND::PageDown => self.scroll_by(page),
+         COMMAND::PageUp => self.scroll_by(-page),
+         COMMAND::PlayPause => return self.run_action(&ACTION::Toggle(self.active_media()), 0.0),
+         COMMAND::Next => {
+            if let Some(m) = self.info.media.get(self.active_media() - 1) {
+               return m
+                  .player
+                  .next()
+                  .map_err(|e| format!("next track failed: {e}"));
+            }
+         }
+         COMMAND::Previous => {
+            if let Some(m) = self.info.media.get(self.active_media() - 1) {
+               return m
+                  .player
+                  .previous()
+                  .map_err(|e| format!("previous track failed: {e}"));
+            }
          }
+         COMMAND::VolumeUp => self.nudge_volume(self.active_media(), 0.05),
+         COMMAND::VolumeDown => self.nudge_volume(self.active_media(), -0.05),
+      }
+      Ok(())
+   }
+
+   fn handle_mouse(&mut self, tui: &mut TUIMutRef, mouse: MouseEvent) {
+      let pos = Position::new(mouse.column, mouse.row);
+      let over_info = self.info_area.get().contains(pos);
+      let hit = self
+         .hits
+         .borrow()
+         .iter()
+         .find(|(r, _)| r.contains(pos))
+         .cloned();
+      let frac = |r: Rect| (mouse.column - r.x) as f64 / r.width.max(1) as f64;
+
+      match (mouse.kind, hit) {
+         (MouseEventKind::Down(MouseButton::Left), Some((rect, action))) => {
+            if let Err(e) = self.run_action(&action, frac(rect)) {
+               self.report(tui, &e);
+            }
+         }
+         (MouseEventKind::ScrollUp, Some((_, ACTION::Volume(i)))) => self.nudge_volume(i, 0.05),
+         (MouseEventKind::ScrollDown, Some((_, ACTION::Volume(i)))) => self.nudge_volume(i, -0.05),
+         (MouseEventKind::ScrollDown, _) if over_info => self.scroll_by(3),
+         (MouseEventKind::ScrollUp, _) if over_info => self.scroll_by(-3),
+         _ => {}
+      }
+   }
+
+   fn run_action(&mut self, action: &ACTION, frac: f64) -> Result<(), String> {
+      let media = |i: usize| i.checked_sub(1).and_then(|i| self.info.media.get(i));
+      match action {
+         ACTION::Call(f) => f
+            .call::<()>(frac)
+            .map_err(|e| format!("on_click failed: {e}")),
+         ACTION::Toggle(i) => match media(*i) {
+            Some(m) => m
+               .player
+               .play_pause()
+               .map_err(|e| format!("play/pause failed: {e}")),
+            None => Ok(()),
+         },
+         ACTION::Seek(i) => match media(*i) {
+            Some(m) if m.can_seek && !m.length.is_zero() => {
+               let target = m.length.as_micros() as f64 * frac;
+               let delta = target as i64 - m.position().as_micros() as i64;
+               m.player
+                  .seek(delta)
+                  .map_err(|e| format!("seek failed: {e}"))
+            }
+            _ => Ok(()),
+         },
+         ACTION::Volume(_) => Ok(()),
+      }
+   }
+
+   // 1-based like the `media` index of span actions
+   fn active_media(&self) -> usize {
+      self.info.media.active().map_or(1, |i| i + 1)
+   }
+
+   fn nudge_volume(&mut self, i: usize, by: f64) {
+      let media = i.checked_sub(1).and_then(|i| self.info.media.get_mut(i));
+      if let Some(m) = media {
+         let volume = (m.volume + by).clamp(0.0, 1.0);
+         if m.player.set_volume(volume).is_ok() {
+            m.volume = volume;
+         }
+      }
+   }
+}
+
+// every way out ends here, whether it was a key, a lua function or the runtime itself
+impl Drop for LoopFetch {
+   fn drop(&mut self) {
+      self.hide_graphics();
+      capture_mouse(false);
+      if let Some(lua) = &self.lua {
+         // nowhere left to log to
+         let _ = call_hook::<()>(lua, "on_exit", ());
       }
    }
 }
@@ -1,2 +1,14 @@
+mod cli;
+mod hooks;
+mod input;
+mod layout;
 mod loopfetch;
+mod theme;
+mod word;
+pub use cli::*;
+pub use hooks::*;
+pub use input::*;
+pub use layout::*;
 pub use loopfetch::*;
+pub use theme::*;
+pub use word::*;
@@ -0,0 +1,70 @@
+use crate::app::config_dir;
+use katatui::*;
+use std::{fs, path::PathBuf};
+
+// a `themes/<name>.lua` of the same name wins
+static THEMES: &[(&str, &str)] = &[
+   ("gruvbox", include_str!("../themes/gruvbox.lua")),
+   ("nord", include_str!("../themes/nord.lua")),
+   ("pastel", include_str!("../themes/pastel.lua")),
+];
+
+pub fn themes_dir() -> PathBuf {
+   config_dir().join("themes")
+}
+
+pub fn theme_names() -> Vec<String> {
+   let mut names: Vec<String> = THEMES.iter().map(|(n, _)| n.to_string()).collect();
+   if let Ok(entries) = fs::read_dir(themes_dir()) {
+      for path in entries.flatten().map(|e| e.path()) {
+         if path.extension().is_some_and(|e| e == "lua") {
+            if let Some(stem) = path.file_stem() {
+               names.push(stem.to_string_lossy().into_owned());
+            }
+         }
+      }
+   }
+   names.sort();
+   names.dedup();
+   names
+}
+
+pub fn next_theme(current: Option<&str>) -> Option<String> {
+   let names = theme_names();
+   let next = match current.and_then(|c| names.iter().position(|n| n == c)) {
+      Some(i) => (i + 1) % names.len(),
+      None => 0,
+   };
+   names.get(next).cloned()
+}
+
+pub fn apply_theme(lua: &mlua::Lua, name: &str) -> Result<(), String> {
+   let path = themes_dir().join(format!("{name}.lua"));
+   let src = match fs::read_to_string(&path) {
+      Ok(src) => src,
+      _ => match THEMES.iter().find(|(n, _)| *n == name) {
+         Some((_, src)) => src.to_string(),
+         None => {
+            return Err(format!(
+               "no theme named {name} in {}",
+               themes_dir().display()
+            ));
+         }
+      },
+   };
+   let theme = lua
+      .load(src)
+      .set_name(name)
+      .eval::<mlua::Table>()
+      .map_err(|e| format!("failed to load theme {name}: {e}"))?;
+
+   let globals = lua.globals();
+   for (key, global) in [("colors", "COLORS"), ("styles", "STYLES")] {
+      if let Ok(t) = theme.get::<mlua::Table>(key) {
+         globals
+            .set(global, t)
+            .map_err(|e| format!("failed to set {global} from theme {name}: {e}"))?;
+      }
+   }
+   Ok(())
+}
@@ -0,0 +1,965 @@
+use crate::art::{BRAILLE, fit_color, hsv_to_rgb, parse_color, rotate};
+use katatui::*;
+use std::cell::RefCell;
+use std::sync::OnceLock;
+use std::time::Instant;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const BAR_WIDTH: usize = 10;
+const BAR_FILL: &str = "█";
+const BAR_EMPTY: &str = "░";
+const GRAPH_WIDTH: usize = 20;
+const ELLIPSIS: &str = "…";
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone)]
+pub struct Bar {
+   pub value: f64,
+   pub max: f64,
+   pub width: usize,
+   pub fill: String,
+   pub empty: String,
+   pub style: Style,
+   pub empty_style: Style,
+}
+
+#[derive(Debug, Clone)]
+pub struct Gauge {
+   pub bar: Bar,
+   pub thresholds: Vec<(f64, Style)>,
+   pub ramp: Vec<Color>,
+   pub label: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PLOT {
+   Spark,
+   Braille,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+   pub plot: PLOT,
+   pub values: Vec<f64>,
+   pub max: Option<f64>,
+   pub width: Option<usize>,
+   pub style: Style,
+}
+
+#[derive(Debug, Clone)]
+pub struct Palette {
+   pub colors: Vec<u8>,
+   pub shape: String,
+}
+
+impl Palette {
+   fn new(colors: Vec<u8>, shape: &str) -> Self {
+      let shape = match shape {
+         "block" => "███",
+         "half" => "▀▀▀",
+         "circle" => "● ",
+         "square" => "■ ",
+         "diamond" => "◆ ",
+         s => s,
+      };
+      Palette {
+         colors,
+         shape: shape.to_string(),
+      }
+   }
+}
+
+pub fn palette_lines() -> LINES {
+   vec![
+      vec![Word::Palette(Palette::new((0..8).collect(), "block"))],
+      vec![Word::Palette(Palette::new((8..16).collect(), "block"))],
+   ]
+}
+
+#[derive(Debug, Clone)]
+pub struct Flow {
+   pub text: String,
+   pub style: Style,
+   pub colors: Vec<Color>,
+   pub speed: f32,
+   pub row: Option<f32>,
+}
+
+impl Flow {
+   fn color_at(&self, frac: f32, t: f32) -> Color {
+      let color = match ramp_color(&self.colors, frac as f64) {
+         Some(c) => rotate(c, t * self.speed),
+         // no colors means rainbow
+         None => {
+            let (r, g, b) = hsv_to_rgb(frac * 360.0 + t * self.speed, 0.6, 1.0);
+            Color::Rgb(r, g, b)
+         }
+      };
+      fit_color(color)
+   }
+
+   fn spans(&self) -> Vec<Span<'_>> {
+      let t = clock();
+      if let Some(row) = self.row {
+         return vec![Span::styled(
+            self.text.as_str(),
+            self.style.fg(self.color_at(row, t)),
+         )];
+      }
+      let n = self.text.graphemes(true).count();
+      self
+         .text
+         .graphemes(true)
+         .enumerate()
+         .map(|(i, g)| {
+            let frac = i as f32 / (n.max(2) - 1) as f32;
+            Span::styled(g, self.style.fg(self.color_at(frac, t)))
+         })
+         .collect()
+   }
+}
+
+// shared so every animated span moves in step
+fn clock() -> f32 {
+   static START: OnceLock<Instant> = OnceLock::new();
+   START.get_or_init(Instant::now).elapsed().as_secs_f32()
+}
+
+#[derive(Debug, Clone)]
+pub enum ACTION {
+   Call(mlua::Function),
+   Toggle(usize),
+   Seek(usize),
+   Volume(usize),
+}
+
+impl ACTION {
+   fn from_table(table: &mlua::Table) -> Option<Self> {
+      if let Ok(f) = table.get::<mlua::Function>("on_click") {
+         return Some(ACTION::Call(f));
+      }
+      let media = table.get::<usize>("media").unwrap_or(1);
+      match table.get::<String>("action").ok()?.as_str() {
+         "toggle" => Some(ACTION::Toggle(media)),
+         "seek" => Some(ACTION::Seek(media)),
+         "volume" => Some(ACTION::Volume(media)),
+         _ => None,
+      }
+   }
+}
+
+#[derive(Debug, Clone)]
+pub enum Word {
+   Text { text: String, style: Style },
+   Bar(Bar),
+   Gauge(Gauge),
+   Graph(Graph),
+   Palette(Palette),
+   Flow(Flow),
+   Act(Box<Word>, ACTION),
+}
+
+impl Word {
+   pub fn new(text: impl Into<String>, style: Style) -> Self {
+      Word::Text {
+         text: text.into(),
+         style,
+      }
+   }
+
+   pub fn width(&self) -> usize {
+      match self {
+         Word::Text { text, .. } => text.width(),
+         Word::Bar(bar) => bar.width,
+         Word::Gauge(gauge) => gauge.bar.width + if gauge.label { 5 } else { 0 },
+         Word::Graph(graph) => graph.width.unwrap_or(GRAPH_WIDTH),
+         Word::Palette(p) => p.shape.width() * p.colors.len(),
+         Word::Flow(flow) => flow.text.width(),
+         Word::Act(word, _) => word.width(),
+      }
+   }
+
+   pub fn flex(&self) -> bool {
+      match self {
+         Word::Act(word, _) => word.flex(),
+         word => matches!(word, Word::Graph(Graph { width: None, .. })),
+      }
+   }
+
+   pub fn spans(&self, width: usize) -> Vec<Span<'_>> {
+      match self {
+         Word::Text { text, style } => vec![Span::styled(text.as_str(), *style)],
+         Word::Bar(bar) => bar.spans(bar.style),
+         Word::Gauge(gauge) => {
+            let mut spans = gauge.bar.spans(gauge.style());
+            if gauge.label {
+               let pct = (gauge.bar.frac() * 100.0).round();
+               spans.push(Span::styled(format!(" {pct:>3}%"), gauge.style()));
+            }
+            spans
+         }
+         Word::Graph(graph) => vec![graph.span(width)],
+         Word::Palette(p) => p
+            .colors
+            .iter()
+            .map(|c| fit_color(Color::Indexed(*c)))
+            .map(|c| Span::styled(p.shape.as_str(), Style::new().fg(c)))
+            .collect(),
+         Word::Flow(flow) => flow.spans(),
+         Word::Act(word, _) => word.spans(width),
+      }
+   }
+}
+
+impl Bar {
+   fn frac(&self) -> f64 {
+      match self.max > 0.0 {
+         true => (self.value / self.max).clamp(0.0, 1.0),
+         _ => 0.0,
+      }
+   }
+
+   fn spans(&self, style: Style) -> Vec<Span<'static>> {
+      let filled = (self.frac() * self.width as f64).round() as usize;
+      vec![
+         Span::styled(self.fill.repeat(filled), style),
+         Span::styled(self.empty.repeat(self.width - filled), self.empty_style),
+      ]
+   }
+}
+
+impl Gauge {
+   fn style(&self) -> Style {
+      let value = self.bar.value;
+      if let Some((_, style)) = self.thresholds.iter().rev().find(|(at, _)| value >= *at) {
+         return *style;
+      }
+      match ramp_color(&self.ramp, self.bar.frac()) {
+         Some(c) => self.bar.style.fg(fit_color(c)),
+         None => self.bar.style,
+      }
+   }
+}
+
+impl Graph {
+   fn span(&self, width: usize) -> Span<'static> {
+      let width = self.width.unwrap_or(width);
+      let per_cell = match self.plot {
+         PLOT::Spark => 1,
+         PLOT::Braille => 2,
+      };
+      let max = self
+         .max
+         .unwrap_or_else(|| self.values.iter().copied().fold(0.0, f64::max))
+         .max(f64::EPSILON);
+      let frac = |v: f64| (v / max).clamp(0.0, 1.0);
+
+      // right align the newest samples, missing ones stay blank
+      let take = (width * per_cell).min(self.values.len());
+      let mut samples = vec![None; width * per_cell - take];
+      samples.extend(
+         self.values[self.values.len() - take..]
+            .iter()
+            .map(|v| Some(frac(*v))),
+      );
+
+      let text: String = match self.plot {
+         PLOT::Spark => samples
+            .iter()
+            .map(|s| match s {
+               Some(f) => SPARKS[((f * 7.0).round() as usize).min(7)],
+               None => ' ',
+            })
+            .collect(),
+         PLOT::Braille => samples
+            .chunks(2)
+            .map(|pair| {
+               let mut bits = 0;
+               for (dx, s) in pair.iter().enumerate() {
+                  if let Some(f) = s {
+                     let level = ((f * 3.0).round() as usize).min(3);
+                     bits |= BRAILLE[dx][3 - level];
+                  }
+               }
+               char::from_u32(0x2800 + bits).unwrap_or(' ')
+            })
+            .collect(),
+      };
+      Span::styled(text, self.style)
+   }
+}
+
+// non-rgb colors can't blend, they switch at the midpoint
+fn ramp_color(ramp: &[Color], frac: f64) -> Option<Color> {
+   if ramp.len() < 2 {
+      return ramp.first().copied();
+   }
+   let pos = frac * (ramp.len() - 1) as f64;
+   let i = (pos.floor() as usize).min(ramp.len() - 2);
+   let t = pos - i as f64;
+   match (ramp[i], ramp[i + 1]) {
+      (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+         let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+         Some(Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2)))
+      }
+      (a, b) => Some(if t < 0.5 { a } else { b }),
+   }
+}
+
+pub type LINES = Vec<Vec<Word>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ALIGN {
+   #[default]
+   Left,
+   Center,
+   Right,
+}
+
+impl ALIGN {
+   pub fn from_name(name: &str) -> Self {
+      match name.chars().next() {
+         Some('c') | Some('C') => ALIGN::Center,
+         Some('r') | Some('R') => ALIGN::Right,
+         _ => ALIGN::Left,
+      }
+   }
+}
+
+#[derive(Debug, Clone)]
+struct FIT {
+   max: Option<usize>,
+   wrap: bool,
+   align: ALIGN,
+   ellipsis: Option<String>,
+   priority: Option<i32>,
+}
+
+pub fn truncate(text: &str, max: usize, ellipsis: &str) -> String {
+   if text.width() <= max {
+      return text.to_string();
+   }
+   let room = max.saturating_sub(ellipsis.width());
+   let mut out = String::new();
+   let mut width = 0;
+   for g in text.graphemes(true) {
+      width += g.width();
+      if width > room {
+         break;
+      }
+      out.push_str(g);
+   }
+   match ellipsis.width() <= max {
+      true => out + ellipsis,
+      _ => out,
+   }
+}
+
+// bars and graphs are dropped whole when they don't fit
+fn truncate_line(words: Vec<Word>, max: usize, ellipsis: &str) -> Vec<Word> {
+   let mut out = Vec::new();
+   let mut width = 0;
+   for word in words {
+      let w = word.width();
+      if width + w <= max {
+         width += w;
+         out.push(word);
+         continue;
+      }
+      if let Some(word) = cut_word(word, max - width, ellipsis) {
+         out.push(word);
+      }
+      break;
+   }
+   out
+}
+
+// clickable spans keep their action when their text gets cut
+fn cut_word(word: Word, room: usize, ellipsis: &str) -> Option<Word> {
+   match word {
+      Word::Text { text, style } => Some(Word::new(truncate(&text, room, ellipsis), style)),
+      Word::Flow(flow) => Some(Word::Flow(Flow {
+         text: truncate(&flow.text, room, ellipsis),
+         ..flow
+      })),
+      Word::Act(word, action) => {
+         cut_word(*word, room, ellipsis).map(|w| Word::Act(Box::new(w), action))
+      }
+      _ if room >= ellipsis.width() => Some(Word::new(ellipsis, Style::new())),
+      _ => None,
+   }
+}
+
+fn wrap_line(words: Vec<Word>, max: usize) -> Vec<Vec<Word>> {
+   let max = max.max(1);
+   let mut lines = vec![Vec::new()];
+   let mut width = 0;
+
+   for word in words {
+      let (text, style) = match word {
+         Word::Text { text, style } => (text, style),
+         word => {
+            if width > 0 && width + word.width() > max {
+               lines.push(Vec::new());
+               width = 0;
+            }
+            width += word.width();
+            lines.last_mut().unwrap().push(word);
+            continue;
+         }
+      };
+      for token in text.split_inclusive(' ') {
+         let mut token = token;
+         if width > 0 && width + token.trim_end().width() > max {
+            lines.push(Vec::new());
+            width = 0;
+         }
+         if width == 0 {
+            token = token.trim_start();
+         }
+         // a token wider than a whole line gets split wherever it has to
+         for g in token.graphemes(true) {
+            if width + g.width() > max {
+               if g.trim().is_empty() {
+                  continue;
+               }
+               if width > 0 {
+                  lines.push(Vec::new());
+                  width = 0;
+               }
+            }
+            width += g.width();
+            let line = lines.last_mut().unwrap();
+            match line.last_mut() {
+               Some(Word::Text { text, style: s }) if *s == style => text.push_str(g),
+               _ => line.push(Word::new(g, style)),
+            }
+         }
+      }
+   }
+   lines
+}
+
+pub fn lines_width(lines: &LINES) -> usize {
+   lines
+      .iter()
+      .map(|words| words.iter().map(Word::width).sum::<usize>())
+      .max()
+      .unwrap_or(0)
+}
+
+pub fn lines_text(lines: &LINES, width: usize) -> Text<'_> {
+   let mut text = Vec::<Line>::new();
+   for line in lines {
+      let share = flex_share(line, width);
+      let mut spans = Vec::<Span>::new();
+      for word in line {
+         spans.extend(word.spans(share));
+      }
+      text.push(Line::from(spans));
+   }
+   Text::from(text)
+}
+
+fn flex_share(line: &[Word], width: usize) -> usize {
+   let fixed: usize = line.iter().filter(|w| !w.flex()).map(Word::width).sum();
+   let flex_n = line.iter().filter(|w| w.flex()).count().max(1);
+   (width.saturating_sub(fixed) / flex_n).max(1)
+}
+
+pub fn word_spots(line: &[Word], width: usize) -> Vec<(usize, usize, &Word)> {
+   let share = flex_share(line, width);
+   let mut x = 0;
+   let mut spots = Vec::new();
+   for word in line {
+      let w = if word.flex() { share } else { word.width() };
+      spots.push((x, w, word));
+      x += w;
+   }
+   spots
+}
+
+pub fn read_lines(lines_table: &mlua::Table, styles: Option<&mlua::Table>) -> LINES {
+   read_lines_with_priority(lines_table, styles, None).0
+}
+
+// lines with a priority can be hidden when space runs out, the lowest first. `wrap` lines
+// without a `max` wrap at `wrap_at`, the box's width on the last frame, so the width
+// returned is what the box asks for before that wrapping
+pub fn read_lines_with_priority(
+   lines_table: &mlua::Table,
+   styles: Option<&mlua::Table>,
+   wrap_at: Option<usize>,
+) -> (LINES, Vec<Option<i32>>, usize) {
+   let mut result = Vec::new();
+   let mut natural = 0;
+
+   for line_res in lines_table.sequence_values::<mlua::Table>() {
+      let line_tbl = match line_res {
+         Ok(tbl) => tbl,
+         _ => continue,
+      };
+      let mut words = Vec::new();
+
+      for span_res in line_tbl.sequence_values::<mlua::Table>() {
+         let span_tbl = match span_res {
+            Ok(tbl) => tbl,
+            _ => continue,
+         };
+         if let Some(word) = read_word(&span_tbl, styles) {
+            words.push(word);
+         }
+      }
+
+      let fit = FIT {
+         max: line_tbl.get("max").unwrap_or(None),
+         wrap: line_tbl.get("wrap").unwrap_or(false),
+         align: match line_tbl.get::<Option<String>>("align") {
+            Ok(Some(name)) => ALIGN::from_name(&name),
+            _ => ALIGN::Left,
+         },
+         ellipsis: line_tbl.get("ellipsis").unwrap_or(None),
+         priority: line_tbl.get("priority").unwrap_or(None),
+      };
+      let ellipsis = fit.ellipsis.as_deref().unwrap_or(ELLIPSIS);
+      let fitted = match (fit.max, wrap_at) {
+         (Some(max), _) if fit.wrap => wrap_line(words, max),
+         (Some(max), _) => vec![truncate_line(words, max, ellipsis)],
+         (None, Some(at)) if fit.wrap => {
+            natural = natural.max(words.iter().map(Word::width).sum());
+            wrap_line(words, at)
+         }
+         (None, _) => vec![words],
+      };
+      // a line's action covers every span that has none of its own
+      let action = ACTION::from_table(&line_tbl);
+      for words in fitted {
+         let words = match &action {
+            Some(action) => words
+               .into_iter()
+               .map(|w| match w {
+                  Word::Act(..) => w,
+                  w => Word::Act(Box::new(w), action.clone()),
+               })
+               .collect(),
+            None => words,
+         };
+         result.push((words, fit.align, fit.priority));
+      }
+   }
+
+   // gradients running across lines take the color of their row
+   let rows = result.len().max(2) - 1;
+   for (y, (words, _, _)) in result.iter_mut().enumerate() {
+      for word in words.iter_mut() {
+         let word = match word {
+            Word::Act(inner, _) => inner.as_mut(),
+            word => word,
+         };
+         if let Word::Flow(Flow { row: Some(row), .. }) = word {
+            *row = y as f32 / rows as f32;
+         }
+      }
+   }
+
+   // aligned lines are padded against the widest line of the box
+   let width = result
+      .iter()
+      .map(|(w, _, _)| w.iter().map(Word::width).sum::<usize>())
+      .max();
+   let natural = natural.max(width.unwrap_or(0));
+   let (lines, priority) = result
+      .into_iter()
+      .map(|(mut words, align, priority)| {
+         let free = width.unwrap_or(0) - words.iter().map(Word::width).sum::<usize>();
+         let pad = match align {
+            ALIGN::Left => 0,
+            ALIGN::Center => free / 2,
+            ALIGN::Right => free,
+         };
+         if pad > 0 {
+            words.insert(0, Word::new(" ".repeat(pad), Style::new()));
+         }
+         (words, priority)
+      })
+      .unzip();
+   (lines, priority, natural)
+}
+
+fn read_word(span_tbl: &mlua::Table, styles: Option<&mlua::Table>) -> Option<Word> {
+   let word = read_plain_word(span_tbl, styles)?;
+   match ACTION::from_table(span_tbl) {
+      Some(action) => Some(Word::Act(Box::new(word), action)),
+      None => Some(word),
+   }
+}
+
+fn read_plain_word(span_tbl: &mlua::Table, styles: Option<&mlua::Table>) -> Option<Word> {
+   if let Ok(value) = span_tbl.get::<mlua::Value>("palette") {
+      let colors = match value {
+         mlua::Value::Table(t) => Some(t.sequence_values::<u8>().flatten().collect()),
+         mlua::Value::String(s) => match s.to_string_lossy().as_str() {
+            "normal" => Some((0..8).collect()),
+            "bright" => Some((8..16).collect()),
+            "all" => Some((0..16).collect()),
+            _ => None,
+         },
+         _ => None,
+      };
+      if let Some(colors) = colors {
+         let shape: String = span_tbl.get("shape").unwrap_or("block".into());
+         return Some(Word::Palette(Palette::new(colors, &shape)));
+      }
+   }
+   for (key, plot) in [("spark", PLOT::Spark), ("graph", PLOT::Braille)] {
+      if let Ok(values) = span_tbl.get::<mlua::Table>(key) {
+         return Some(Word::Graph(Graph {
+            plot,
+            values: values.sequence_values::<f64>().flatten().collect(),
+            max: span_tbl.get("max").ok().flatten(),
+            width: span_tbl.get("width").ok().flatten(),
+            style: match span_tbl.get::<mlua::Value>("style") {
+               Ok(v) => read_style_value(v, styles),
+               _ => Style::new(),
+            },
+         }));
+      }
+   }
+   if let Ok(Some(value)) = span_tbl.get::<Option<f64>>("gauge") {
+      let thresholds = match span_tbl.get::<mlua::Table>("thresholds") {
+         Ok(t) => t
+            .sequence_values::<mlua::Table>()
+            .flatten()
+            .filter_map(|stop| {
+               let at = stop.get::<f64>(1).ok()?;
+               Some((at, read_style_value(stop.get(2).ok()?, styles)))
+            })
+            .collect(),
+         _ => Vec::new(),
+      };
+      let ramp = match span_tbl.get::<mlua::Table>("ramp") {
+         Ok(t) => t
+            .sequence_values::<String>()
+            .flatten()
+            .filter_map(|c| parse_color(&c))
+            .collect(),
+         _ => Vec::new(),
+      };
+      return Some(Word::Gauge(Gauge {
+         bar: read_bar(span_tbl, value, styles),
+         thresholds,
+         ramp,
+         label: span_tbl.get("label").unwrap_or(true),
+      }));
+   }
+   if let Ok(Some(value)) = span_tbl.get::<Option<f64>>("bar") {
+      return Some(Word::Bar(read_bar(span_tbl, value, styles)));
+   }
+
+   let mut text: String = span_tbl.get("text").unwrap_or_default();
+   let style = match span_tbl.get::<mlua::Value>("style") {
+      Ok(mlua::Value::Nil) | Err(_) => return None,
+      Ok(v) => read_style_value(v, styles),
+   };
+   if let Ok(Some(max)) = span_tbl.get::<Option<usize>>("max") {
+      let ellipsis: Option<String> = span_tbl.get("ellipsis").unwrap_or(None);
+      text = truncate(&text, max, ellipsis.as_deref().unwrap_or(ELLIPSIS));
+      // a span with a max width can also sit aligned inside that width
+      if let Ok(Some(align)) = span_tbl.get::<Option<String>>("align") {
+         let free = max.saturating_sub(text.width());
+         let (l, r) = match ALIGN::from_name(&align) {
+            ALIGN::Left => (0, free),
+            ALIGN::Center => (free / 2, free - free / 2),
+            ALIGN::Right => (free, 0),
+         };
+         text = format!("{}{text}{}", " ".repeat(l), " ".repeat(r));
+      }
+   }
+   match flow_of(span_tbl, styles) {
+      Some((colors, speed, across_lines)) => Some(Word::Flow(Flow {
+         text,
+         style,
+         colors,
+         speed,
+         row: across_lines.then_some(0.0),
+      })),
+      None => Some(Word::Text { text, style }),
+   }
+}
+
+fn flow_of(
+   span_tbl: &mlua::Table,
+   styles: Option<&mlua::Table>,
+) -> Option<(Vec<Color>, f32, bool)> {
+   let style_tbl = match span_tbl.get::<mlua::Value>("style") {
+      Ok(mlua::Value::Table(t)) => t,
+      Ok(mlua::Value::String(name)) => styles?.get::<mlua::Table>(name.to_string_lossy()).ok()?,
+      _ => return None,
+   };
+   let colors: Vec<Color> = match style_tbl.get::<mlua::Table>("gradient") {
+      Ok(t) => t
+         .sequence_values::<String>()
+         .flatten()
+         .filter_map(|c| parse_color(&c))
+         .collect(),
+      _ => Vec::new(),
+   };
+   let rainbow = style_tbl.get("rainbow").unwrap_or(false);
+   let speed: f32 = style_tbl.get("speed").unwrap_or(0.0);
+   if colors.is_empty() && !rainbow && speed == 0.0 {
+      return None;
+   }
+   // a plain style with just a speed turns its own fg
+   let colors = match (colors.is_empty(), rainbow, read_style(&style_tbl).fg) {
+      (true, false, Some(fg)) => vec![fg],
+      _ => colors,
+   };
+   let across: String = style_tbl.get("across").unwrap_or_default();
+   Some((colors, speed, across.starts_with('l')))
+}
+
+fn read_bar(span_tbl: &mlua::Table, value: f64, styles: Option<&mlua::Table>) -> Bar {
+   let style_of = |key: &str| match span_tbl.get::<mlua::Value>(key) {
+      Ok(v) => read_style_value(v, styles),
+      _ => Style::new(),
+   };
+   Bar {
+      value,
+      max: span_tbl.get("max").unwrap_or(100.0),
+      width: span_tbl.get("width").unwrap_or(BAR_WIDTH),
+      fill: span_tbl.get("fill").unwrap_or(BAR_FILL.into()),
+      empty: span_tbl.get("empty").unwrap_or(BAR_EMPTY.into()),
+      style: style_of("style"),
+      empty_style: style_of("empty_style"),
+   }
+}
+
+pub fn read_style_value(value: mlua::Value, styles: Option<&mlua::Table>) -> Style {
+   match value {
+      mlua::Value::Table(t) => read_style(&t),
+      mlua::Value::String(s) => {
+         let name = s.to_string_lossy();
+         match styles.and_then(|st| st.get::<mlua::Table>(name.as_str()).ok()) {
+            Some(t) => read_style(&t),
+            None => match parse_color(&name) {
+               Some(c) => Style::new().fg(fit_color(c)),
+               None => Style::new(),
+            },
+         }
+      }
+      _ => Style::new(),
+   }
+}
+
+fn read_color(value: mlua::Value) -> Option<Color> {
+   let color = match value {
+      mlua::Value::Integer(n) => Color::Indexed(n.clamp(0, 255) as u8),
+      mlua::Value::Number(n) => Color::Indexed(n.clamp(0.0, 255.0) as u8),
+      mlua::Value::String(s) => parse_color(&s.to_string_lossy())?,
+      _ => return None,
+   };
+   Some(fit_color(color))
+}
+
+thread_local! {
+   static STYLE_ERRORS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// reading a style can't fail, what was wrong with it is kept until taken
+pub fn take_style_errors() -> Vec<String> {
+   STYLE_ERRORS.with_borrow_mut(std::mem::take)
+}
+
+fn style_error(e: String) {
+   STYLE_ERRORS.with_borrow_mut(|errors| {
+      if !errors.contains(&e) {
+         errors.push(e);
+      }
+   });
+}
+
+pub fn read_style(style_tbl: &mlua::Table) -> Style {
+   let mut style = Style::new();
+   let color = |key: &str| read_color(style_tbl.get(key).unwrap_or(mlua::Value::Nil));
+
+   if let Some(fg) = color("fg") {
+      style = style.fg(fg);
+   }
+   if let Some(bg) = color("bg") {
+      style = style.bg(bg);
+   }
+   if let Some(ul) = color("underline_color") {
+      style = style.underline_color(ul);
+   }
+
+   for (key, modifier) in [
+      ("bold", Modifier::BOLD),
+      ("italic", Modifier::ITALIC),
+      ("dim", Modifier::DIM),
+      ("reverse", Modifier::REVERSED),
+      ("strikethrough", Modifier::CROSSED_OUT),
+      ("hidden", Modifier::HIDDEN),
+   ] {
+      if let Ok(true) = style_tbl.get(key) {
+         style = style.add_modifier(modifier);
+      }
+   }
+
+   let blink = match style_tbl.get::<mlua::Value>("blink") {
+      Ok(mlua::Value::Boolean(true)) => Some(Modifier::SLOW_BLINK),
+      Ok(mlua::Value::String(s)) => match s.to_string_lossy().as_str() {
+         "slow" => Some(Modifier::SLOW_BLINK),
+         "rapid" | "fast" => Some(Modifier::RAPID_BLINK),
+         "none" => None,
+         other => {
+            style_error(format!("unknown blink '{other}', use slow, rapid or none"));
+            None
+         }
+      },
+      _ => None,
+   };
+   // the backend only draws single underlines
+   let underline = match style_tbl.get::<mlua::Value>("underline") {
+      Ok(mlua::Value::Boolean(true)) => true,
+      Ok(mlua::Value::String(s)) => match s.to_string_lossy().as_str() {
+         "single" => true,
+         "none" => false,
+         other @ ("double" | "curly" | "dotted" | "dashed") => {
+            style_error(format!("underline '{other}' can't be drawn, only single"));
+            false
+         }
+         other => {
+            style_error(format!("unknown underline '{other}', use single or none"));
+            false
+         }
+      },
+      _ => false,
+   };
+   if let Some(blink) = blink {
+      style = style.add_modifier(blink);
+   }
+   if underline {
+      style = style.add_modifier(Modifier::UNDERLINED);
+   }
+
+   style
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn text(words: &[Word]) -> String {
+      words
+         .iter()
+         .map(|w| match w {
+            Word::Act(w, _) => text(std::slice::from_ref(w.as_ref())),
+            Word::Text { text, .. } => text.clone(),
+            Word::Flow(flow) => flow.text.clone(),
+            w => "#".repeat(w.width()),
+         })
+         .collect()
+   }
+
+   #[test]
+   fn truncates_by_width() {
+      assert_eq!(truncate("hi", 5, "…"), "hi");
+      assert_eq!(truncate("hello world", 5, "…"), "hell…");
+      assert_eq!(truncate("日本語", 4, "…"), "日…");
+      assert_eq!(truncate("hello", 0, "…"), "");
+      assert_eq!(truncate("hello", 4, ""), "hell");
+   }
+
+   #[test]
+   fn truncate_line_keeps_actions() {
+      let words = vec![
+         Word::new("abc", Style::new()),
+         Word::Act(
+            Box::new(Word::new("defgh", Style::new())),
+            ACTION::Toggle(1),
+         ),
+      ];
+      let out = truncate_line(words, 6, "…");
+      assert_eq!(text(&out), "abcde…");
+      assert!(matches!(&out[1], Word::Act(_, ACTION::Toggle(1))));
+   }
+
+   #[test]
+   fn truncate_line_cuts_flows_and_drops_palettes() {
+      let flow = Flow {
+         text: "rainbow".into(),
+         style: Style::new(),
+         colors: Vec::new(),
+         speed: 0.0,
+         row: None,
+      };
+      assert_eq!(text(&truncate_line(vec![Word::Flow(flow)], 4, "…")), "rai…");
+      let palette = Word::Palette(Palette::new(vec![1, 2], "block"));
+      let out = truncate_line(vec![Word::new("ab", Style::new()), palette], 5, "…");
+      assert_eq!(text(&out), "ab…");
+   }
+
+   #[test]
+   fn wraps_on_spaces() {
+      let lines = wrap_line(vec![Word::new("one two three", Style::new())], 7);
+      let lines: Vec<String> = lines.iter().map(|l| text(l)).collect();
+      assert_eq!(lines, ["one two", "three"]);
+   }
+
+   #[test]
+   fn wraps_long_tokens_anywhere() {
+      let lines = wrap_line(vec![Word::new("abcdefghij", Style::new())], 4);
+      let lines: Vec<String> = lines.iter().map(|l| text(l)).collect();
+      assert_eq!(lines, ["abcd", "efgh", "ij"]);
+   }
+
+   #[test]
+   fn wrap_without_max_uses_the_box_width() {
+      let lua = mlua::Lua::new();
+      let table: mlua::Table = lua
+         .load(r#"{ { { text = "aaa bbb ccc" }, wrap = true }, { { text = "12345" } } }"#)
+         .eval()
+         .unwrap();
+      let (lines, _, width) = read_lines_with_priority(&table, None, Some(7));
+      assert_eq!(lines.len(), 3);
+      assert_eq!(width, 11);
+      let (lines, _, _) = read_lines_with_priority(&table, None, None);
+      assert_eq!(lines.len(), 2);
+   }
+
+   fn style(src: &str) -> (Style, Vec<String>) {
+      let lua = mlua::Lua::new();
+      let table: mlua::Table = lua.load(src).eval().unwrap();
+      take_style_errors();
+      (read_style(&table), take_style_errors())
+   }
+
+   #[test]
+   fn style_attributes() {
+      let (s, errors) = style(r#"{ bold = true, italic = false, underline = true, blink = true }"#);
+      assert!(errors.is_empty());
+      assert_eq!(
+         s.add_modifier,
+         Modifier::BOLD | Modifier::UNDERLINED | Modifier::SLOW_BLINK
+      );
+   }
+
+   #[test]
+   fn style_blink_and_underline_values() {
+      let (s, _) = style(r#"{ blink = "slow", underline = "single" }"#);
+      assert_eq!(s.add_modifier, Modifier::SLOW_BLINK | Modifier::UNDERLINED);
+      let (s, _) = style(r#"{ blink = "rapid" }"#);
+      assert_eq!(s.add_modifier, Modifier::RAPID_BLINK);
+      let (s, errors) = style(r#"{ blink = "none", underline = "none" }"#);
+      assert_eq!(s.add_modifier, Modifier::empty());
+      assert!(errors.is_empty());
+   }
+
+   #[test]
+   fn style_rejects_unknown_values() {
+      let (s, errors) = style(r#"{ underline = "curly", blink = "sometimes" }"#);
+      assert_eq!(s.add_modifier, Modifier::empty());
+      assert_eq!(errors.len(), 2);
+   }
+}
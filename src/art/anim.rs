@@ -0,0 +1,122 @@
+use crate::app::{ANIM, EFFECT, LINES, Word};
+use crate::art::{ansi256_to_rgb, fit_color, hsv_to_rgb, rgb_to_256, rgb_to_hsv};
+use katatui::*;
+use std::f32::consts::TAU;
+
+pub const WAVE_AMP: usize = 2;
+const TYPE_CPS: f32 = 40.0;
+const TYPE_HOLD: f32 = 2.0;
+const HUE_DPS: f32 = 90.0;
+// red, yellow, green, cyan, blue, magenta in ansi order
+const HUES: [u8; 6] = [1, 3, 2, 6, 4, 5];
+
+pub fn effect_width(anim: &ANIM) -> usize {
+   match anim.effect {
+      EFFECT::Wave => WAVE_AMP,
+      _ => 0,
+   }
+}
+
+pub fn frame_at<'a>(frames: &'a [LINES], anim: &ANIM, t: f32) -> Option<&'a LINES> {
+   match frames.len() {
+      0 => None,
+      n => frames.get((t * anim.fps.max(0.0)) as usize % n),
+   }
+}
+
+pub fn animate(lines: &LINES, anim: &ANIM, t: f32) -> Text<'static> {
+   let t = t * anim.speed;
+   let mut budget = match anim.effect {
+      EFFECT::Typewriter => {
+         let total: usize = lines.iter().flatten().map(|w| w.width()).sum();
+         let cycle = total as f32 / TYPE_CPS + TYPE_HOLD;
+         ((t % cycle) * TYPE_CPS) as usize
+      }
+      _ => usize::MAX,
+   };
+
+   let mut text = Vec::new();
+   for (y, line) in lines.iter().enumerate() {
+      let mut spans = Vec::new();
+      if anim.effect == EFFECT::Wave {
+         let pad = ((t * TAU + y as f32 * 0.6).sin() + 1.0) * WAVE_AMP as f32 / 2.0;
+         spans.push(Span::raw(" ".repeat(pad.round() as usize)));
+      }
+      for word in line {
+         let (text, style) = match word {
+            Word::Text { text, style } => (text, *style),
+            _ => {
+               // bars and gauges only show once the typewriter reaches them
+               let n = word.width().min(budget);
+               budget -= n;
+               if n == word.width() {
+                  spans.extend(word.spans(word.width()).into_iter().map(owned));
+               }
+               continue;
+            }
+         };
+         let n = text.chars().count().min(budget);
+         budget -= n;
+         let style = match anim.effect {
+            EFFECT::Cycle => shift_hue(style, t * HUE_DPS),
+            _ => style,
+         };
+         spans.push(Span::styled(
+            text.chars().take(n).collect::<String>(),
+            style,
+         ));
+      }
+      text.push(Line::from(spans));
+   }
+   Text::from(text)
+}
+
+fn owned(span: Span<'_>) -> Span<'static> {
+   Span::styled(span.content.into_owned(), span.style)
+}
+
+fn shift_hue(style: Style, deg: f32) -> Style {
+   match style.fg {
+      Some(c) => style.fg(fit_color(rotate(c, deg))),
+      None => style,
+   }
+}
+
+// the 16 ansi colors step around the color wheel
+pub fn rotate(color: Color, deg: f32) -> Color {
+   let hue = |r, g, b| {
+      let (h, s, v) = rgb_to_hsv(r, g, b);
+      hsv_to_rgb(h + deg, s, v)
+   };
+   let index = match color {
+      Color::Rgb(r, g, b) => {
+         let (r, g, b) = hue(r, g, b);
+         return Color::Rgb(r, g, b);
+      }
+      Color::Indexed(n) => match ansi256_to_rgb(n) {
+         Some((r, g, b)) => {
+            let (r, g, b) = hue(r, g, b);
+            return Color::Indexed(rgb_to_256(r, g, b));
+         }
+         None => n,
+      },
+      Color::Red => 1,
+      Color::Green => 2,
+      Color::Yellow => 3,
+      Color::Blue => 4,
+      Color::Magenta => 5,
+      Color::Cyan => 6,
+      Color::LightRed => 9,
+      Color::LightGreen => 10,
+      Color::LightYellow => 11,
+      Color::LightBlue => 12,
+      Color::LightMagenta => 13,
+      Color::LightCyan => 14,
+      other => return other,
+   };
+   let bright = index & 8;
+   match HUES.iter().position(|h| *h == index & 7) {
+      Some(i) => Color::Indexed(HUES[(i + (deg / 60.0) as usize) % HUES.len()] | bright),
+      None => color,
+   }
+}
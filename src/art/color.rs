@@ -0,0 +1,253 @@
+use crate::app::{DEPTH, no_color};
+use katatui::*;
+use std::sync::OnceLock;
+
+pub fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+   const STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+   let cube = |v: u8| -> usize {
+      STEPS
+         .iter()
+         .enumerate()
+         .min_by_key(|(_, s)| (**s as i32 - v as i32).abs())
+         .map(|(i, _)| i)
+         .unwrap_or(0)
+   };
+   let (ci, gi, bi) = (cube(r), cube(g), cube(b));
+   let cube_rgb = (STEPS[ci], STEPS[gi], STEPS[bi]);
+
+   let avg = (r as u32 + g as u32 + b as u32) / 3;
+   let gray_i = ((avg as i32 - 8).max(0) / 10).min(23) as u8;
+   let gray_v = 8 + gray_i * 10;
+
+   match dist((r, g, b), (gray_v, gray_v, gray_v)) < dist((r, g, b), cube_rgb) {
+      true => 232 + gray_i,
+      _ => 16 + (36 * ci + 6 * gi + bi) as u8,
+   }
+}
+
+fn dist(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+   let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+   d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+pub fn luma(r: u8, g: u8, b: u8) -> u8 {
+   ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+}
+
+// `None` for the 16 ansi colors, the terminal themes those itself
+pub fn ansi256_to_rgb(n: u8) -> Option<(u8, u8, u8)> {
+   const STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+   match n {
+      0..=15 => None,
+      16..=231 => {
+         let i = (n - 16) as usize;
+         Some((STEPS[i / 36], STEPS[i / 6 % 6], STEPS[i % 6]))
+      }
+      _ => {
+         let v = 8 + (n - 232) * 10;
+         Some((v, v, v))
+      }
+   }
+}
+
+pub fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+   let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+   let max = r.max(g).max(b);
+   let min = r.min(g).min(b);
+   let d = max - min;
+   let h = match d == 0.0 {
+      true => 0.0,
+      _ if max == r => 60.0 * ((g - b) / d).rem_euclid(6.0),
+      _ if max == g => 60.0 * ((b - r) / d + 2.0),
+      _ => 60.0 * ((r - g) / d + 4.0),
+   };
+   let s = match max == 0.0 {
+      true => 0.0,
+      _ => d / max,
+   };
+   (h, s, max)
+}
+
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+   let h = h.rem_euclid(360.0);
+   let c = v * s;
+   let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+   let m = v - c;
+   let (r, g, b) = match h as u32 / 60 {
+      0 => (c, x, 0.0),
+      1 => (x, c, 0.0),
+      2 => (0.0, c, x),
+      3 => (0.0, x, c),
+      4 => (x, 0.0, c),
+      _ => (c, 0.0, x),
+   };
+   let to = |f: f32| ((f + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+   (to(r), to(g), to(b))
+}
+
+pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+   let v = l + s * l.min(1.0 - l);
+   let sv = match v == 0.0 {
+      true => 0.0,
+      _ => 2.0 * (1.0 - l / v),
+   };
+   hsv_to_rgb(h, sv, v)
+}
+
+pub fn parse_color(s: &str) -> Option<Color> {
+   let s = s.trim().to_lowercase();
+   if let Some(hex) = s.strip_prefix('#') {
+      let hex = match hex.len() {
+         3 => hex.chars().flat_map(|c| [c, c]).collect(),
+         6 => hex.to_string(),
+         _ => return None,
+      };
+      let v = u32::from_str_radix(&hex, 16).ok()?;
+      return Some(Color::Rgb((v >> 16) as u8, (v >> 8) as u8, v as u8));
+   }
+   if let Ok(n) = s.parse::<u8>() {
+      return Some(Color::Indexed(n));
+   }
+   let args = |prefix: &str| -> Option<Vec<f32>> {
+      let inner = s
+         .strip_prefix(prefix)?
+         .strip_prefix('(')?
+         .strip_suffix(')')?;
+      inner
+         .split(',')
+         .map(|a| a.trim().trim_end_matches('%').parse::<f32>().ok())
+         .collect()
+   };
+   if let Some([r, g, b]) = args("rgb").as_deref() {
+      let c = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+      return Some(Color::Rgb(c(*r), c(*g), c(*b)));
+   }
+   if let Some([h, sat, l]) = args("hsl").as_deref() {
+      let (r, g, b) = hsl_to_rgb(*h, sat / 100.0, l / 100.0);
+      return Some(Color::Rgb(r, g, b));
+   }
+   let name = s.replace(['-', ' '], "_");
+   let name = name.replace("light_", "bright_");
+   Some(match name.as_str() {
+      "reset" | "default" => Color::Reset,
+      "black" => Color::Black,
+      "red" => Color::Red,
+      "green" => Color::Green,
+      "yellow" => Color::Yellow,
+      "blue" => Color::Blue,
+      "magenta" => Color::Magenta,
+      "cyan" => Color::Cyan,
+      "white" => Color::Gray,
+      "bright_black" | "gray" | "grey" => Color::DarkGray,
+      "bright_red" => Color::LightRed,
+      "bright_green" => Color::LightGreen,
+      "bright_yellow" => Color::LightYellow,
+      "bright_blue" => Color::LightBlue,
+      "bright_magenta" => Color::LightMagenta,
+      "bright_cyan" => Color::LightCyan,
+      "bright_white" => Color::White,
+      _ => return None,
+   })
+}
+
+pub fn color_support() -> DEPTH {
+   static SUPPORT: OnceLock<DEPTH> = OnceLock::new();
+   *SUPPORT.get_or_init(|| {
+      let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+      let term = std::env::var("TERM").unwrap_or_default();
+      if colorterm == "truecolor" || colorterm == "24bit" {
+         DEPTH::TrueColor
+      } else if term.contains("256") {
+         DEPTH::Ansi256
+      } else {
+         DEPTH::Ansi16
+      }
+   })
+}
+
+pub fn fit_color(color: Color) -> Color {
+   if no_color() {
+      return Color::Reset;
+   }
+   let support = color_support();
+   let rgb = match color {
+      Color::Rgb(r, g, b) => (r, g, b),
+      Color::Indexed(n) if support == DEPTH::Ansi16 => match ansi256_to_rgb(n) {
+         Some(rgb) => rgb,
+         None => return color,
+      },
+      _ => return color,
+   };
+   match support {
+      DEPTH::TrueColor => color,
+      DEPTH::Ansi256 => Color::Indexed(rgb_to_256(rgb.0, rgb.1, rgb.2)),
+      DEPTH::Ansi16 => Color::Indexed(rgb_to_16(rgb)),
+   }
+}
+
+// measured against the usual xterm defaults
+pub fn rgb_to_16(rgb: (u8, u8, u8)) -> u8 {
+   const ANSI: [(u8, u8, u8); 16] = [
+      (0, 0, 0),
+      (205, 0, 0),
+      (0, 205, 0),
+      (205, 205, 0),
+      (0, 0, 238),
+      (205, 0, 205),
+      (0, 205, 205),
+      (229, 229, 229),
+      (127, 127, 127),
+      (255, 0, 0),
+      (0, 255, 0),
+      (255, 255, 0),
+      (92, 92, 255),
+      (255, 0, 255),
+      (0, 255, 255),
+      (255, 255, 255),
+   ];
+   (0..16u8)
+      .min_by_key(|i| dist(rgb, ANSI[*i as usize]))
+      .unwrap_or(7)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn hex_colors() {
+      assert_eq!(parse_color("#ff8000"), Some(Color::Rgb(255, 128, 0)));
+      assert_eq!(parse_color("#F80"), Some(Color::Rgb(255, 136, 0)));
+      assert_eq!(parse_color("#ff80"), None);
+      assert_eq!(parse_color("#gggggg"), None);
+   }
+
+   #[test]
+   fn named_and_indexed_colors() {
+      assert_eq!(parse_color("red"), Some(Color::Red));
+      assert_eq!(parse_color(" Bright-Blue "), Some(Color::LightBlue));
+      assert_eq!(parse_color("light_green"), Some(Color::LightGreen));
+      assert_eq!(parse_color("white"), Some(Color::Gray));
+      assert_eq!(parse_color("208"), Some(Color::Indexed(208)));
+      assert_eq!(parse_color("256"), None);
+      assert_eq!(parse_color("mauve"), None);
+   }
+
+   #[test]
+   fn function_colors() {
+      assert_eq!(
+         parse_color("rgb(255, 182, 193)"),
+         Some(Color::Rgb(255, 182, 193))
+      );
+      assert_eq!(parse_color("rgb(300, -5, 0)"), Some(Color::Rgb(255, 0, 0)));
+      assert_eq!(
+         parse_color("hsl(0, 100%, 50%)"),
+         Some(Color::Rgb(255, 0, 0))
+      );
+      assert_eq!(
+         parse_color("hsl(120, 100%, 25%)"),
+         Some(Color::Rgb(0, 128, 0))
+      );
+      assert_eq!(parse_color("rgb(1, 2)"), None);
+   }
+}
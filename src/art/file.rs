@@ -0,0 +1,44 @@
+use crate::app::{LINES, PICTURE, config_dir};
+use crate::art::{is_image, parse_art, picture_frames};
+use katatui::*;
+use std::{
+   fs,
+   path::{Path, PathBuf},
+};
+
+pub fn resolve_path(file: &str) -> PathBuf {
+   if let Some(rest) = file.strip_prefix("~/") {
+      if let Ok(home) = std::env::var("HOME") {
+         return Path::new(&home).join(rest);
+      }
+   }
+   let path = PathBuf::from(file);
+   match path.is_absolute() {
+      true => path,
+      _ => config_dir().join(path),
+   }
+}
+
+pub fn read_art(path: &Path) -> Result<String, String> {
+   fs::read_to_string(path).map_err(|e| format!("failed to read logo {}: {e}", path.display()))
+}
+
+pub fn dir_frames(dir: &Path, pic: &PICTURE, styles: &[Style]) -> Result<Vec<LINES>, String> {
+   let entries =
+      fs::read_dir(dir).map_err(|e| format!("failed to read frames {}: {e}", dir.display()))?;
+   let mut paths: Vec<PathBuf> = entries
+      .flatten()
+      .map(|e| e.path())
+      .filter(|p| p.is_file())
+      .collect();
+   paths.sort();
+
+   let mut frames = Vec::new();
+   for path in paths {
+      match is_image(&path) {
+         true => frames.extend(picture_frames(&path, pic)?),
+         _ => frames.push(parse_art(&read_art(&path)?, styles)),
+      }
+   }
+   Ok(frames)
+}
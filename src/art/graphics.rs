@@ -0,0 +1,275 @@
+use crate::app::GRAPHICS;
+use crate::art::load_rgba;
+use image::{RgbaImage, imageops::FilterType};
+use katatui::*;
+use std::{
+   collections::BTreeMap,
+   io::{IsTerminal, Read, Write},
+   path::PathBuf,
+   sync::OnceLock,
+   time::{Duration, Instant, SystemTime},
+};
+
+const KITTY_ID: u32 = 2077;
+const QUERY_ID: u32 = 31;
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+const CHUNK: usize = 4096;
+const B64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PROTOCOL {
+   Kitty,
+   Sixel,
+}
+
+impl GRAPHICS {
+   pub fn protocol(&self) -> Option<PROTOCOL> {
+      match self {
+         GRAPHICS::Kitty => Some(PROTOCOL::Kitty),
+         GRAPHICS::Sixel => Some(PROTOCOL::Sixel),
+         GRAPHICS::Off => None,
+         GRAPHICS::Auto => detect_protocol(),
+      }
+   }
+}
+
+static DETECTED: OnceLock<Option<PROTOCOL>> = OnceLock::new();
+
+// has to run before the tui takes over stdin
+pub fn probe_graphics() {
+   DETECTED.get_or_init(|| query_protocol().unwrap_or_else(env_protocol));
+}
+
+fn detect_protocol() -> Option<PROTOCOL> {
+   *DETECTED.get_or_init(env_protocol)
+}
+
+// kitty answers its own query before the DA1 answer, which every terminal sends.
+// sixel support is attribute 4 in DA1
+fn query_protocol() -> Option<Option<PROTOCOL>> {
+   if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+      return None;
+   }
+   let reply = with_raw_mode(|| {
+      emit(&format!(
+         "\x1b_Gi={QUERY_ID},s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[c"
+      ))
+      .ok()?;
+      read_reply(QUERY_TIMEOUT)
+   })?;
+   if reply.contains(&format!("\x1b_Gi={QUERY_ID};OK")) {
+      return Some(Some(PROTOCOL::Kitty));
+   }
+   let da1 = da1_attrs(&reply)?;
+   match da1.split(';').any(|a| a == "4") {
+      true => Some(Some(PROTOCOL::Sixel)),
+      _ => Some(None),
+   }
+}
+
+fn da1_attrs(reply: &str) -> Option<&str> {
+   let start = reply.rfind("\x1b[?")? + 3;
+   let len = reply[start..].find('c')?;
+   Some(&reply[start..start + len])
+}
+
+fn read_reply(timeout: Duration) -> Option<String> {
+   let deadline = Instant::now() + timeout;
+   let mut reply = Vec::new();
+   let mut buf = [0u8; 256];
+   loop {
+      let left = deadline
+         .saturating_duration_since(Instant::now())
+         .as_millis() as i32;
+      let mut fd = libc::pollfd {
+         fd: libc::STDIN_FILENO,
+         events: libc::POLLIN,
+         revents: 0,
+      };
+      // SAFETY: a single valid pollfd for the duration of the call
+      if left <= 0 || unsafe { libc::poll(&mut fd, 1, left) } <= 0 {
+         return None;
+      }
+      let n = std::io::stdin().lock().read(&mut buf).ok()?;
+      reply.extend_from_slice(&buf[..n]);
+      let text = String::from_utf8_lossy(&reply);
+      if da1_attrs(&text).is_some() {
+         return Some(text.into_owned());
+      }
+   }
+}
+
+fn with_raw_mode<T>(f: impl FnOnce() -> Option<T>) -> Option<T> {
+   // SAFETY: termios is plain data, tcgetattr fills it before it's used
+   let mut old: libc::termios = unsafe { std::mem::zeroed() };
+   if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut old) } != 0 {
+      return None;
+   }
+   let mut raw = old;
+   // SAFETY: raw is a valid termios copied from the terminal's own
+   unsafe { libc::cfmakeraw(&mut raw) };
+   if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+      return None;
+   }
+   let out = f();
+   // SAFETY: restores the settings read above
+   unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &old) };
+   out
+}
+
+fn env_protocol() -> Option<PROTOCOL> {
+   let var = |k: &str| std::env::var(k).unwrap_or_default().to_lowercase();
+   let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+   if !var("KITTY_WINDOW_ID").is_empty()
+      || term.contains("kitty")
+      || term.contains("ghostty")
+      || program == "wezterm"
+      || program == "ghostty"
+   {
+      return Some(PROTOCOL::Kitty);
+   }
+   if ["foot", "mlterm", "contour", "yaft", "sixel"]
+      .iter()
+      .any(|t| term.contains(t))
+      || program == "iterm.app"
+      || !var("WT_SESSION").is_empty()
+   {
+      return Some(PROTOCOL::Sixel);
+   }
+   None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+   pub protocol: PROTOCOL,
+   pub path: PathBuf,
+   pub modified: Option<SystemTime>,
+   pub area: Rect,
+   pub screen: Rect,
+}
+
+// ratatui never sees the pixels, the caller has to mark the covered cells as skipped
+pub fn show(placement: &Placement, cell: (u16, u16)) -> Result<(), String> {
+   let img = load_rgba(&placement.path)?;
+   let area = placement.area;
+   let (w, h) = (
+      (area.width as u32 * cell.0 as u32).max(1),
+      (area.height as u32 * cell.1 as u32).max(1),
+   );
+   let img = image::imageops::resize(&img, w, h, FilterType::Triangle);
+   let seq = match placement.protocol {
+      PROTOCOL::Kitty => kitty(&img, area.width, area.height),
+      PROTOCOL::Sixel => sixel(&img),
+   };
+   emit(&format!(
+      "\x1b7\x1b[{};{}H{seq}\x1b8",
+      area.y + 1,
+      area.x + 1
+   ))
+}
+
+// kitty images outlive their cells, sixel pixels are wiped once ratatui redraws
+// the skipped cells
+pub fn hide(placement: &Placement) {
+   if placement.protocol == PROTOCOL::Kitty {
+      let _ = emit(&format!("\x1b_Ga=d,d=I,i={KITTY_ID},q=2\x1b\\"));
+   }
+}
+
+// writes past the ratatui backend. this happens during render, before ratatui writes the
+// frame's diff, and the previous frame was already flushed by then, so nothing of ratatui's
+// is half written. the cursor is saved and restored around images, and their cells are
+// skipped, so the diff written afterwards never lands on top of them.
+fn emit(seq: &str) -> Result<(), String> {
+   let mut out = std::io::stdout().lock();
+   out.write_all(seq.as_bytes())
+      .and_then(|_| out.flush())
+      .map_err(|e| format!("failed to write image: {e}"))
+}
+
+fn kitty(img: &RgbaImage, cols: u16, rows: u16) -> String {
+   let data = base64(img.as_raw());
+   let chunks: Vec<&[u8]> = data.as_bytes().chunks(CHUNK).collect();
+   let mut out = String::new();
+   for (i, chunk) in chunks.iter().enumerate() {
+      let more = (i + 1 < chunks.len()) as u8;
+      let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+      let head = match i {
+         0 => format!(
+            "a=T,q=2,C=1,f=32,i={KITTY_ID},s={},v={},c={cols},r={rows},m={more}",
+            img.width(),
+            img.height()
+         ),
+         _ => format!("m={more}"),
+      };
+      out.push_str(&format!("\x1b_G{head};{chunk}\x1b\\"));
+   }
+   out
+}
+
+fn sixel(img: &RgbaImage) -> String {
+   let (w, h) = img.dimensions();
+   let mut out = format!("\x1bP0;1;0q\"1;1;{w};{h}");
+   for i in 0..216u32 {
+      let (r, g, b) = (i / 36, i / 6 % 6, i % 6);
+      out.push_str(&format!("#{i};2;{};{};{}", r * 20, g * 20, b * 20));
+   }
+   let level = |v: u8| (v as u32 * 5 + 127) / 255;
+
+   for band in (0..h).step_by(6) {
+      let mut colors: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+      for x in 0..w {
+         for dy in 0..6 {
+            let y = band + dy;
+            if y >= h {
+               break;
+            }
+            let [r, g, b, a] = img.get_pixel(x, y).0;
+            if a < 128 {
+               continue;
+            }
+            let c = level(r) * 36 + level(g) * 6 + level(b);
+            colors.entry(c).or_insert_with(|| vec![0; w as usize])[x as usize] |= 1 << dy;
+         }
+      }
+      for (c, bits) in colors {
+         out.push_str(&format!("#{c}"));
+         let mut run = 0;
+         for (x, b) in bits.iter().enumerate() {
+            run += 1;
+            if bits.get(x + 1) == Some(b) {
+               continue;
+            }
+            let ch = (63 + b) as char;
+            match run > 3 {
+               true => out.push_str(&format!("!{run}{ch}")),
+               _ => (0..run).for_each(|_| out.push(ch)),
+            }
+            run = 0;
+         }
+         out.push('$');
+      }
+      out.push('-');
+   }
+   out.push_str("\x1b\\");
+   out
+}
+
+fn base64(data: &[u8]) -> String {
+   let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+   for chunk in data.chunks(3) {
+      let b = [
+         chunk[0],
+         *chunk.get(1).unwrap_or(&0),
+         *chunk.get(2).unwrap_or(&0),
+      ];
+      let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+      for i in 0..4 {
+         match i <= chunk.len() {
+            true => out.push(B64[(n >> (18 - 6 * i) & 63) as usize] as char),
+            _ => out.push('='),
+         }
+      }
+   }
+   out
+}
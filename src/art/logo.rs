@@ -0,0 +1,120 @@
+use crate::app::{LINES, LOGO, SIZE, SOURCE};
+use crate::art::{dir_frames, fit_color, parse_art, picture_frames, read_art};
+use katatui::*;
+
+// art uses neofetch's `${c1}`..`${c9}`, `colors` are the ansi indices behind them
+pub struct LogoDef {
+   pub name: &'static str,
+   pub ids: &'static [&'static str],
+   pub colors: &'static [u8],
+   pub large: &'static str,
+   pub small: &'static str,
+}
+
+macro_rules! logo {
+   ($name:literal, [$($id:literal),*], [$($c:literal),*]) => {
+      LogoDef {
+         name: $name,
+         ids: &[$($id),*],
+         colors: &[$($c),*],
+         large: include_str!(concat!("../logos/", $name, ".txt")),
+         small: include_str!(concat!("../logos/", $name, "_small.txt")),
+      }
+   };
+}
+
+pub static LOGOS: &[LogoDef] = &[
+   logo!("arch", ["arch", "archarm"], [6, 6, 7, 1]),
+   logo!("debian", ["debian", "raspbian"], [1, 7, 3]),
+   logo!(
+      "ubuntu",
+      ["ubuntu", "kubuntu", "xubuntu", "lubuntu"],
+      [1, 7, 3]
+   ),
+   logo!("fedora", ["fedora"], [12, 7]),
+   logo!("nixos", ["nixos"], [4, 6]),
+   logo!("gentoo", ["gentoo"], [5, 7]),
+   logo!("manjaro", ["manjaro", "manjaro-arm"], [2]),
+   logo!("endeavouros", ["endeavouros"], [1, 5, 4]),
+   logo!(
+      "opensuse",
+      ["opensuse", "opensuse-leap", "opensuse-tumbleweed", "suse"],
+      [2, 7]
+   ),
+   logo!("mint", ["linuxmint"], [2, 7]),
+   logo!("pop", ["pop"], [6, 7]),
+   logo!("alpine", ["alpine"], [4, 5, 7, 6]),
+];
+
+pub static TUX: LogoDef = logo!("linux", ["linux"], [7, 8, 3]);
+
+impl LogoDef {
+   pub fn art(&self, size: SIZE) -> &'static str {
+      match size {
+         SIZE::Large => self.large,
+         SIZE::Small => self.small,
+      }
+   }
+
+   pub fn styles(&self, overrides: &[Color]) -> Vec<Style> {
+      art_styles(overrides, self.colors)
+   }
+}
+
+pub fn art_styles(overrides: &[Color], fallback: &[u8]) -> Vec<Style> {
+   (0..9)
+      .map(|i| {
+         let color = overrides
+            .get(i)
+            .copied()
+            .or(fallback.get(i).map(|c| Color::Indexed(*c)));
+         let style = Style::new().add_modifier(Modifier::BOLD);
+         match color {
+            Some(c) => style.fg(fit_color(c)),
+            None => style,
+         }
+      })
+      .collect()
+}
+
+pub fn find_logo(name: &str) -> Option<&'static LogoDef> {
+   LOGOS
+      .iter()
+      .chain(std::iter::once(&TUX))
+      .find(|l| l.name == name || l.ids.contains(&name))
+}
+
+pub fn detect_logo(os_id: &str, os_like: &[String]) -> &'static LogoDef {
+   let ids = std::iter::once(os_id).chain(os_like.iter().map(String::as_str));
+   for id in ids {
+      if let Some(logo) = LOGOS.iter().find(|l| l.ids.contains(&id)) {
+         return logo;
+      }
+   }
+   &TUX
+}
+
+// frames are padded to the same height so the box doesn't jump
+pub fn logo_frames(logo: &LOGO, os_id: &str, os_like: &[String]) -> Result<Vec<LINES>, String> {
+   let styles = art_styles(&logo.colors, &[]);
+   let mut frames = match &logo.source {
+      SOURCE::Off => Vec::new(),
+      SOURCE::File(path) => vec![parse_art(&read_art(path)?, &styles)],
+      SOURCE::Image(path, pic) => picture_frames(path, pic)?,
+      SOURCE::Dir(path, pic) => dir_frames(path, pic, &styles)?,
+      SOURCE::Frames(srcs) => srcs.iter().map(|src| parse_art(src, &styles)).collect(),
+      SOURCE::Auto => {
+         let def = detect_logo(os_id, os_like);
+         vec![parse_art(def.art(logo.size), &def.styles(&logo.colors))]
+      }
+      SOURCE::Named(name) => {
+         let def = find_logo(name).unwrap_or_else(|| detect_logo(os_id, os_like));
+         vec![parse_art(def.art(logo.size), &def.styles(&logo.colors))]
+      }
+   };
+   let height = frames.iter().map(Vec::len).max().unwrap_or(0);
+   for frame in &mut frames {
+      frame.resize(height, Vec::new());
+   }
+   Ok(frames)
+}
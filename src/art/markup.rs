@@ -0,0 +1,73 @@
+use crate::app::{LINES, Word};
+use crate::art::fit_color;
+use katatui::*;
+
+const TAB: &str = "    ";
+
+// like in neofetch, the active style carries over line breaks
+pub fn parse_art(src: &str, styles: &[Style]) -> LINES {
+   let mut lines = LINES::new();
+   let mut style = styles.first().copied().unwrap_or_default();
+   for raw in src.lines() {
+      let raw = raw.replace('\t', TAB);
+      let mut words = Vec::new();
+      let mut text = String::new();
+      let mut rest = raw.as_str();
+      while let Some(pos) = rest.find("${") {
+         let tail = &rest[pos + 2..];
+         let next = tail
+            .find('}')
+            .and_then(|end| tag_style(&tail[..end], styles).map(|style| (style, &tail[end + 1..])));
+         match next {
+            Some((next, after)) => {
+               text.push_str(&rest[..pos]);
+               if !text.is_empty() {
+                  words.push(Word::new(std::mem::take(&mut text), style));
+               }
+               style = next;
+               rest = after;
+            }
+            None => {
+               text.push_str(&rest[..pos + 2]);
+               rest = tail;
+            }
+         }
+      }
+      text.push_str(rest);
+      if !text.is_empty() {
+         words.push(Word::new(text, style));
+      }
+      lines.push(words);
+   }
+   lines
+}
+
+fn tag_style(tag: &str, styles: &[Style]) -> Option<Style> {
+   let tag = tag.trim();
+   if tag == "reset" {
+      return Some(Style::new());
+   }
+   if let Some(n) = tag.strip_prefix('c') {
+      return match n.parse::<usize>() {
+         Ok(i @ 1..=9) => Some(styles.get(i - 1).copied().unwrap_or_default()),
+         _ => None,
+      };
+   }
+   let mut parts = tag.split(',').map(str::trim);
+   let style = match parts.next() {
+      Some(fg) if is_hex(fg) => Style::new().fg(fit_color(hex_to_rgb(fg))),
+      _ => return None,
+   };
+   match parts.next() {
+      None => Some(style),
+      Some(bg) if is_hex(bg) => Some(style.bg(fit_color(hex_to_rgb(bg)))),
+      _ => None,
+   }
+}
+
+fn is_hex(s: &str) -> bool {
+   match s.strip_prefix('#') {
+      Some(h) => h.len() == 6 && h.chars().all(|c| c.is_ascii_hexdigit()),
+      None => false,
+   }
+}
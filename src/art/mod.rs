@@ -0,0 +1,14 @@
+mod anim;
+mod color;
+mod file;
+mod graphics;
+mod logo;
+mod markup;
+mod picture;
+pub use anim::*;
+pub use color::*;
+pub use file::*;
+pub use graphics::*;
+pub use logo::*;
+pub use markup::*;
+pub use picture::*;
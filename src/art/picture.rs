@@ -0,0 +1,230 @@
+use crate::app::{DEPTH, LINES, MODE, PICTURE, Word};
+use crate::art::{fit_color, luma, rgb_to_16, rgb_to_256};
+use image::{AnimationDecoder, RgbaImage, codecs::gif::GifDecoder, imageops::FilterType};
+use katatui::*;
+use std::{fmt::Display, fs::File, io::BufReader, path::Path};
+
+const DEFAULT_WIDTH: u32 = 32;
+const RAMP: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+const QUADS: [char; 16] = [
+   ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+// dot bits by column then row
+pub const BRAILLE: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+const OPAQUE: u8 = 128;
+
+type RGB = (u8, u8, u8);
+
+impl MODE {
+   fn cell(&self) -> (u32, u32) {
+      match self {
+         MODE::Half => (1, 2),
+         MODE::Quadrant => (2, 2),
+         MODE::Braille => (2, 4),
+         MODE::Ascii => (1, 1),
+      }
+   }
+}
+
+pub fn picture_frames(path: &Path, pic: &PICTURE) -> Result<Vec<LINES>, String> {
+   let images = match has_ext(path, &["gif"]) {
+      true => gif_frames(path)?,
+      _ => vec![load_rgba(path)?],
+   };
+   Ok(images.iter().map(|img| rgba_lines(img, pic)).collect())
+}
+
+pub fn is_image(path: &Path) -> bool {
+   has_ext(path, &["png", "jpg", "jpeg", "gif"])
+}
+
+fn has_ext(path: &Path, exts: &[&str]) -> bool {
+   match path.extension() {
+      Some(ext) => exts.contains(&ext.to_string_lossy().to_lowercase().as_str()),
+      None => false,
+   }
+}
+
+fn rgba_lines(img: &RgbaImage, pic: &PICTURE) -> LINES {
+   let (cols, rows) = fit(img.width(), img.height(), pic);
+   let (sx, sy) = pic.mode.cell();
+   let img = image::imageops::resize(img, cols * sx, rows * sy, FilterType::Triangle);
+   let threshold = mean_luma(&img);
+
+   let mut lines = LINES::new();
+   for row in 0..rows {
+      let mut words: Vec<Word> = Vec::new();
+      for col in 0..cols {
+         let (ch, style) = match pic.mode {
+            MODE::Half => half_cell(&img, col, row, pic.depth),
+            MODE::Quadrant => quad_cell(&img, col, row, pic.depth),
+            MODE::Braille => braille_cell(&img, col, row, threshold, pic.depth),
+            MODE::Ascii => ascii_cell(&img, col, row, pic.depth),
+         };
+         match words.last_mut() {
+            Some(Word::Text { text, style: s }) if *s == style => text.push(ch),
+            _ => words.push(Word::new(ch.to_string(), style)),
+         }
+      }
+      lines.push(words);
+   }
+   lines
+}
+
+fn load_err(path: &Path, e: impl Display) -> String {
+   format!("failed to load image {}: {e}", path.display())
+}
+
+fn gif_frames(path: &Path) -> Result<Vec<RgbaImage>, String> {
+   let file = File::open(path).map_err(|e| load_err(path, e))?;
+   let decoder = GifDecoder::new(BufReader::new(file)).map_err(|e| load_err(path, e))?;
+   let frames = decoder
+      .into_frames()
+      .collect_frames()
+      .map_err(|e| load_err(path, e))?;
+   Ok(frames.into_iter().map(|f| f.into_buffer()).collect())
+}
+
+pub fn load_rgba(path: &Path) -> Result<RgbaImage, String> {
+   image::open(path)
+      .map(|img| img.to_rgba8())
+      .map_err(|e| load_err(path, e))
+}
+
+// cells are roughly twice as tall as they are wide
+fn fit(img_w: u32, img_h: u32, pic: &PICTURE) -> (u32, u32) {
+   let aspect = img_h.max(1) as f32 / img_w.max(1) as f32;
+   let from_h = |h: u16| ((h as f32 * 2.0 / aspect).round() as u32).max(1);
+   let cols = match (pic.width, pic.height) {
+      (Some(w), Some(h)) => (w as u32).min(from_h(h)),
+      (Some(w), None) => w as u32,
+      (None, Some(h)) => from_h(h),
+      (None, None) => DEFAULT_WIDTH,
+   }
+   .max(1);
+   let rows = ((cols as f32 * aspect / 2.0).round() as u32).max(1);
+   let rows = match pic.height {
+      Some(h) => rows.min(h as u32),
+      None => rows,
+   };
+   (cols, rows)
+}
+
+fn pixel(img: &RgbaImage, x: u32, y: u32) -> Option<RGB> {
+   let [r, g, b, a] = img.get_pixel(x, y).0;
+   match a >= OPAQUE {
+      true => Some((r, g, b)),
+      _ => None,
+   }
+}
+
+// `depth` can only lower what the terminal supports, `fit_color` does the rest
+fn color(rgb: RGB, depth: DEPTH) -> Color {
+   fit_color(match depth {
+      DEPTH::TrueColor => Color::Rgb(rgb.0, rgb.1, rgb.2),
+      DEPTH::Ansi256 => Color::Indexed(rgb_to_256(rgb.0, rgb.1, rgb.2)),
+      DEPTH::Ansi16 => Color::Indexed(rgb_to_16(rgb)),
+   })
+}
+
+fn average(px: &[RGB]) -> Option<RGB> {
+   if px.is_empty() {
+      return None;
+   }
+   let n = px.len() as u32;
+   let sum = px.iter().fold((0, 0, 0), |(r, g, b), p| {
+      (r + p.0 as u32, g + p.1 as u32, b + p.2 as u32)
+   });
+   Some(((sum.0 / n) as u8, (sum.1 / n) as u8, (sum.2 / n) as u8))
+}
+
+fn mean_luma(img: &RgbaImage) -> u8 {
+   let px: Vec<RGB> = img
+      .pixels()
+      .filter(|p| p.0[3] >= OPAQUE)
+      .map(|p| (p.0[0], p.0[1], p.0[2]))
+      .collect();
+   average(&px).map(|(r, g, b)| luma(r, g, b)).unwrap_or(128)
+}
+
+fn half_cell(img: &RgbaImage, col: u32, row: u32, depth: DEPTH) -> (char, Style) {
+   let top = pixel(img, col, row * 2);
+   let bot = pixel(img, col, row * 2 + 1);
+   match (top, bot) {
+      (Some(t), Some(b)) => ('▀', Style::new().fg(color(t, depth)).bg(color(b, depth))),
+      (Some(t), None) => ('▀', Style::new().fg(color(t, depth))),
+      (None, Some(b)) => ('▄', Style::new().fg(color(b, depth))),
+      (None, None) => (' ', Style::new()),
+   }
+}
+
+fn quad_cell(img: &RgbaImage, col: u32, row: u32, depth: DEPTH) -> (char, Style) {
+   let px: Vec<Option<RGB>> = (0..4)
+      .map(|i| pixel(img, col * 2 + i % 2, row * 2 + i / 2))
+      .collect();
+   let opaque: Vec<RGB> = px.iter().flatten().copied().collect();
+   let all = match average(&opaque) {
+      Some(c) => c,
+      None => return (' ', Style::new()),
+   };
+   // transparent corners can't take a bg color, so the opaque ones become the glyph
+   if opaque.len() < px.len() {
+      let mask = px
+         .iter()
+         .enumerate()
+         .filter(|(_, p)| p.is_some())
+         .fold(0, |mask, (i, _)| mask | 1 << i);
+      return (QUADS[mask], Style::new().fg(color(all, depth)));
+   }
+
+   let mid = luma(all.0, all.1, all.2);
+   let mut mask = 0;
+   let (mut fg, mut bg) = (Vec::new(), Vec::new());
+   for (i, rgb) in opaque.iter().enumerate() {
+      match luma(rgb.0, rgb.1, rgb.2) >= mid {
+         true => {
+            mask |= 1 << i;
+            fg.push(*rgb);
+         }
+         _ => bg.push(*rgb),
+      }
+   }
+   let mut style = Style::new();
+   if let Some(c) = average(&fg) {
+      style = style.fg(color(c, depth));
+   }
+   if let Some(c) = average(&bg) {
+      style = style.bg(color(c, depth));
+   }
+   (QUADS[mask], style)
+}
+
+fn braille_cell(img: &RgbaImage, col: u32, row: u32, threshold: u8, depth: DEPTH) -> (char, Style) {
+   let mut bits = 0;
+   let mut on = Vec::new();
+   for (dx, column) in BRAILLE.iter().enumerate() {
+      for (dy, bit) in column.iter().enumerate() {
+         if let Some(rgb) = pixel(img, col * 2 + dx as u32, row * 4 + dy as u32) {
+            if luma(rgb.0, rgb.1, rgb.2) >= threshold {
+               bits |= *bit;
+               on.push(rgb);
+            }
+         }
+      }
+   }
+   let ch = char::from_u32(0x2800 + bits).unwrap_or(' ');
+   match average(&on) {
+      Some(c) => (ch, Style::new().fg(color(c, depth))),
+      None => (ch, Style::new()),
+   }
+}
+
+fn ascii_cell(img: &RgbaImage, col: u32, row: u32, depth: DEPTH) -> (char, Style) {
+   match pixel(img, col, row) {
+      Some(rgb) => {
+         let i = luma(rgb.0, rgb.1, rgb.2) as usize * (RAMP.len() - 1) / 255;
+         (RAMP[i], Style::new().fg(color(rgb, depth)))
+      }
+      None => (' ', Style::new()),
+   }
+}
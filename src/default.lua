@@ -5,6 +5,31 @@ SETTINGS = {
     rps = 5, -- rate of fetch refreshes (per second)
     order = { "info", "ascii" }, -- order of boxes: info, ascii (a, i also works)
     layout = "horizontal", -- stacking of boxes: horizontal, vertical (or h, v)
+    -- or a tree of rows (dir = "h") and columns (dir = "v") holding any boxes, each box shows
+    -- its lines global (MEDIA_LINES for "media"), size = n, "40%" or "fill", plus min/max
+    -- layout = {
+    --     dir = "v",
+    --     { dir = "h", "ascii", "info", { box = "media", size = "30%" } },
+    --     "palette", -- color blocks, or fill PALETTE_LINES with { palette = .., shape = .. } spans
+    --     { box = "graph", lines = "GRAPH_LINES", size = 4 },
+    -- },
+    anchor = "center", -- where it all sits: top-left, top, top-right, left, center, right, bottom-left, ...
+    margin = 0, -- space kept free around the screen edge: n, { x, y } or { l, r, t, b }
+    offset = { 0, 0 }, -- nudge from the anchor in cells, { x, y }
+    gap = 0, -- cells between boxes
+    sticky = 0, -- lines kept on top while the info box scrolls (j/k, PgUp/PgDn, mouse wheel)
+    overlay = false, -- perf overlay with frame, tick, lua and per-fetcher timings plus recent errors (o toggles)
+    responsive = true, -- on small terminals: flip the layout, then drop the logo, then hide lines by priority
+    logo = "auto", -- distro logo: auto, none, or a name like arch, nixos_small
+    -- logo = { name = "arch", size = "small", colors = { "#1793D1", "#FFFFFF" } },
+    -- logo = { file = "art.txt", colors = { "#FFB6C1", "#77DD77" } }, -- ${c1}..${c9} or ${#rrggbb} markup
+    -- logo = { image = "logo.png", mode = "half", depth = "truecolor", width = 32 }, -- half, quadrant, braille, ascii
+    -- without width or height an image fills the room next to the info box, and follows resizes
+    -- logo = { frames = "frames/", fps = 12 }, -- a directory of art/image frames, or a gif as image
+    -- logo = { name = "arch", effect = "cycle", speed = 1 }, -- effects: cycle, wave, typewriter
+    graphics = "auto", -- real images for image logos: auto, kitty, sixel, none
+    -- graphics = { protocol = "sixel", cell = { 10, 20 } }, -- cell = pixel size of one cell
+    -- theme = "nord", -- COLORS and STYLES from themes/<name>.lua or a built in one (pastel, gruvbox, nord), t cycles
     vars = { comp = "idk" }, -- hardcode unfetchables (e.g. comp = 'picom')
 }
 
@@ -41,31 +66,98 @@ STYLES = {
     pastel12 = { fg = COLORS.PASTEL_ORANGE, bold = true },
 
     border = { fg = "#FFFFFF", bold = true },
+    -- colors can also be "#rgb", ansi names ("red", "bright_blue"), 256-color indices (208),
+    -- "rgb(255, 182, 193)" or "hsl(350, 100%, 86%)", truecolor is downgraded if COLORTERM lacks it
+    -- attributes: bold, italic, dim, reverse, strikethrough, hidden, blink (true, "slow" or "rapid"),
+    -- underline (true or "single", the terminal can't draw other kinds) and underline_color
+    -- link = { fg = "bright_cyan", underline = true, underline_color = 208 },
+
+    -- colored per character at render time: gradient = { colors } or rainbow = true,
+    -- speed turns the hue (degrees per second), across = "lines" runs the gradient down the box
+    -- rainbow = { rainbow = true, speed = 90, bold = true },
+    -- sunset = { gradient = { COLORS.PASTEL_PINK, COLORS.PASTEL_ORANGE, COLORS.PASTEL_GOLD } },
+}
+
+-- box frames by box name, styles by name or table: border (plain, rounded, double, thick), border_style, title,
+-- title_align, title_style, padding and margin (n, { x, y } or { l, r, t, b })
+SETTINGS.boxes = {
+    info = { border = "rounded", border_style = "border", title = " loopfetch ", title_style = "pastel1", padding = { 1, 0 } },
+    -- ascii = { border = "plain", margin = { 1, 0 } },
 }
 
+--========== LOOPFETCH KEYS ==========-- uncomment to rebind, this replaces the whole default map
+-- keys are chords like "q", "Q", "ctrl+r", "alt+left", "space", "pagedown", "f5", "+" (or "plus")
+-- actions: quit, reload, debug, swap_layout, swap_order, next_theme, overlay, scroll_down, scroll_up,
+-- page_down, page_up, play_pause, next, previous, volume_up, volume_down, or a lua function
+-- KEYS = {
+--     q = "quit", d = "debug", r = "reload", t = "next_theme", o = "overlay",
+--     up = "swap_layout", down = "swap_layout", left = "swap_order", right = "swap_order",
+--     j = "scroll_down", k = "scroll_up", pagedown = "page_down", pageup = "page_up",
+--     space = "play_pause", n = "next", p = "previous", ["+"] = "volume_up", ["-"] = "volume_down",
+--     ["ctrl+g"] = function() SETTINGS.gap = (SETTINGS.gap + 1) % 3 end,
+-- }
+
+--========== LOOPFETCH HOOKS ==========-- optional, called when something happens instead of every tick
+-- function on_start() end -- once, after the first fetch
+-- function on_reload() end -- after init.lua was run again (r)
+-- function on_refresh(changed) end -- after a fetch refresh, changed = names of FETCH fields that changed
+-- function on_resize(w, h) end -- the terminal was resized
+-- function on_key(key) return false end -- any key as "q", "ctrl+r", "pagedown", true swallows it
+-- function on_media_change(old, new) end -- the active player's FETCH.media entry before and after a track change, nil for none
+-- function on_exit() end -- right before quitting
+
 function tick()
     -- helper functions
+    -- styles can be given by name, they follow the active theme
     local function span(text, style_name)
-        return { text = text, style = STYLES[style_name] }
+        return { text = text, style = style_name }
     end
 
     local function line(...)
-        local spans = { ... }
-        table.insert(spans, 1, span("|", "pastel2"))
-        table.insert(spans, span("|", "pastel2"))
-        return spans
+        return { ... }
+    end
+    -- lines also take fit options: max (columns), wrap (at max, or at the box width without it),
+    -- ellipsis, align (left, center, right)
+    -- e.g. local l = line(span(FETCH.kern, "pastel2")); l.max = 30; l.wrap = true
+    -- priority = n lets a line be hidden when the terminal is too small, lowest first
+    local function optional(priority, l)
+        l.priority = priority
+        return l
+    end
+
+    -- bars fill to value/max, gauges also pick their color from thresholds or a ramp
+    local function bar(value, max, style_name)
+        return { bar = value, max = max, width = 12, style = style_name, empty_style = "pastel11" }
+    end
+
+    local function gauge(value, max)
+        return {
+            gauge = value, max = max, width = 12, label = true,
+            thresholds = { { 0, "pastel2" }, { 60, "pastel8" }, { 85, "pastel10" } },
+            -- ramp = { COLORS.PASTEL_GREEN, COLORS.PASTEL_YELLOW, COLORS.PASTEL_PINK },
+        }
+    end
+
+    -- graphs of FETCH.history.*, without a width they stretch over the rest of the line
+    local function spark(values, max, style_name)
+        return { spark = values, max = max, style = style_name }
+    end
+
+    local function graph(values, style_name)
+        return { graph = values, style = style_name }
     end
 
     -- regenerate info lines
     FETCH_LINES = {
-        line(span("fps: ", "pastel1"), span(TUI.fps, "pastel2"), span(TUI.frame, "pastel2")),
-        line(span("tps: ", "pastel1"), span(TUI.tps, "pastel2"), span(TUI.tick, "pastel2")),
-        line(span("elapsed: ", "pastel1"), span(TUI.elapsed, "pastel2")),
+        optional(1, line(span("fps: ", "pastel1"), span(TUI.fps, "pastel2"), span(TUI.frame, "pastel2"))),
+        optional(1, line(span("tps: ", "pastel1"), span(TUI.tps, "pastel2"), span(TUI.tick, "pastel2"))),
+        optional(1, line(span("elapsed: ", "pastel1"), span(TUI.elapsed, "pastel2"))),
 
+        -- with the rainbow style above: line(span("User: ", "pastel1"), span(FETCH.user, "rainbow")),
         line(span("User: ", "pastel1"), span(FETCH.user, "pastel2")),
         line(span("Host: ", "pastel3"), span(FETCH.host, "pastel4")),
         line(span("Device: ", "pastel5"), span(FETCH.device, "pastel6")),
-        line(span("BIOS: ", "pastel7"), span(FETCH.bios, "pastel8")),
+        optional(2, line(span("BIOS: ", "pastel7"), span(FETCH.bios, "pastel8"))),
         line(span("Uptime: ", "pastel9"), span(FETCH.uptime, "pastel10")),
         line(span("OS: ", "pastel1"), span(FETCH.os_n .. " " .. FETCH.os_v, "pastel11")),
         line(span("Kernel: ", "pastel2"), span(FETCH.kern, "pastel12")),
@@ -77,13 +169,17 @@ function tick()
         line(span("Shell: ", "pastel3"), span(FETCH.shell, "pastel4")),
         line(span("Text Editor: ", "pastel5"), span(FETCH.text_e, "pastel6")),
         line(span("CPU: ", "pastel7"), span(FETCH.cpu_n .. " (" .. FETCH.cpu_c .. " cores)", "pastel8")),
-        line(span("CPU Usage: ", "pastel9"), span(FETCH.cpu_u .. "%", "pastel10")),
+        line(span("CPU Usage: ", "pastel9"), gauge(FETCH.cpu_u, 100)),
+        line(span("CPU History: ", "pastel7"), spark(FETCH.history.cpu_u, 100, "pastel8")),
+        line(span("Net: ", "pastel3"), graph(FETCH.history.net_rx, "pastel5")),
         line(span("CPU Temp: ", "pastel1"), span(FETCH.cpu_t .. "°C", "pastel2")),
-        line(span("RAM: ", "pastel3"), span(FETCH.ram.avail .. "/" .. FETCH.ram.total, "pastel4")),
+        line(span("RAM: ", "pastel3"), bar(FETCH.ram.avail, FETCH.ram.total, "pastel4"), span(" " .. FETCH.ram.avail .. "/" .. FETCH.ram.total, "pastel4")),
         line(span("GPU: ", "pastel5"), span(FETCH.gpu_n, "pastel6")),
         line(span("GPU Freq: ", "pastel7"), span(FETCH.gpu_f .. "GHz", "pastel8")),
         line(span("GPU Temp: ", "pastel9"), span(FETCH.gpu_t .. "°C", "pastel10")),
-        line(span("VRAM: ", "pastel1"), span(FETCH.vram.avail .. "/" .. FETCH.vram.total, "pastel2")),
+        line(span("VRAM: ", "pastel1"), bar(FETCH.vram.avail, FETCH.vram.total, "pastel2"), span(" " .. FETCH.vram.avail .. "/" .. FETCH.vram.total, "pastel2")),
+        optional(3, line(span("Colors: ", "pastel3"), { palette = "normal", shape = "circle" })),
+        optional(3, line(span("        ", "pastel3"), { palette = "bright", shape = "circle" })),
     }
 
     -- disks
@@ -98,20 +194,42 @@ function tick()
         )
     end
 
+    -- ascii box: nil shows SETTINGS.logo, or fill it like FETCH_LINES
+    ASCII_LINES = nil
+    -- e.g. swap the logo for the playing song
+    -- local m = FETCH.media[1]
+    -- if m and not m.paused then
+    --     ASCII_LINES = { { span(m.song, "pastel4") }, { span(m.artist, "pastel3") } }
+    -- end
+
     -- media
+    local function clock(secs)
+        secs = math.floor(secs)
+        return string.format("%d:%02d", math.floor(secs / 60), secs % 60)
+    end
+
     if FETCH.media and #FETCH.media > 0 then
         for i = 1, #FETCH.media do
             local m = FETCH.media[i]
             local status = m.paused and "paused" or "playing"
-            table.insert(
-                    FETCH_LINES,
-                    line(
-                            span("Media: ", "pastel3"),
-                            span(m.artist .. " - " .. m.song, "pastel4"),
-                            span(" [" .. status .. "]", "pastel5"),
-                            span(" " .. m.name, "pastel6")
-                    )
+            if m.shuffle then status = status .. " shuffle" end
+            if m.looping ~= "none" then status = status .. " loop:" .. m.looping end
+            -- clicking the line plays/pauses, the bar seeks and the wheel over vol changes volume
+            local media_line = line(
+                    span("Media: ", "pastel3"),
+                    -- long titles get cut with an ellipsis instead of widening the box
+                    { text = m.artist .. " - " .. m.song, style = "pastel4", max = 40 },
+                    span(" [" .. status .. "]", "pastel5"),
+                    span(" " .. clock(m.position) .. " ", "pastel7"),
+                    { bar = m.position, max = m.length, width = 12, style = "pastel7", empty_style = "pastel11", media = i, action = "seek" },
+                    span(" " .. clock(m.length), "pastel7"),
+                    { text = " vol " .. math.floor(m.volume * 100) .. "%", style = "pastel8", media = i, action = "volume" },
+                    span(" " .. m.name, "pastel6")
             )
+            media_line.media = i
+            media_line.action = "toggle"
+            table.insert(FETCH_LINES, media_line)
+            -- any span or line can also run lua: on_click = function(frac) ... end
         end
     end
 end
@@ -1,4 +1,5 @@
 use crate::app::SETTINGS;
+use crate::fetch::{HISTORY, Sample};
 use katatui::mlua;
 use katatui::mlua::prelude::{LuaResult, LuaTable};
 use katatui::*;
@@ -6,14 +7,47 @@ use libmacchina::{
    traits::{GeneralReadout as _, ProductReadout as _, ShellFormat, ShellKind}, GeneralReadout,
    ProductReadout,
 };
-use mpris::{Player, PlayerFinder};
+use mpris::{LoopStatus, Player, PlayerFinder};
 use nvml_wrapper::{
    enum_wrappers::device::{Clock, TemperatureSensor},
    Nvml,
 };
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{fs, path::Path};
-use sysinfo::{Disks, System};
+use sysinfo::{Disks, Networks, ProcessesToUpdate, System};
+
+pub const MODULES: [(&str, &str); 30] = [
+   ("user", "user name"),
+   ("host", "host name"),
+   ("device", "device model"),
+   ("bios", "bios version"),
+   ("uptime", "seconds since boot"),
+   ("os_n", "os name"),
+   ("os_v", "os version"),
+   ("os_id", "distro id, like arch"),
+   ("os_like", "distros this one is based on"),
+   ("kern", "kernel version"),
+   ("log_m", "login manager"),
+   ("desk_e", "desktop environment, nil without one"),
+   ("win_m", "window manager"),
+   ("win_p", "window protocol, x11 or wayland"),
+   ("comp", "compositor, from SETTINGS.vars.comp"),
+   ("term", "terminal"),
+   ("shell", "shell"),
+   ("text_e", "text editor from $EDITOR"),
+   ("cpu_n", "cpu model"),
+   ("cpu_c", "cpu cores"),
+   ("cpu_u", "cpu usage in percent"),
+   ("cpu_t", "cpu temperature"),
+   ("ram", "memory { avail, total }"),
+   ("gpu_n", "gpu model"),
+   ("gpu_f", "gpu clock"),
+   ("gpu_t", "gpu temperature"),
+   ("vram", "gpu memory { avail, total }"),
+   ("disks", "mounted disks { mnt, name, mem }"),
+   ("history", "recent cpu, ram, gpu, network and disk readings"),
+   ("media", "mpris players and what they play"),
+];
 
 #[derive(Debug, Default)]
 pub struct Disk {
@@ -32,6 +66,13 @@ impl Mem {
    fn from(avail: u64, total: u64) -> Self {
       Self { avail, total }
    }
+
+   fn percent(&self) -> f64 {
+      match self.total {
+         0 => 0.0,
+         t => self.avail as f64 / t as f64 * 100.0,
+      }
+   }
 }
 
 #[derive(Debug)]
@@ -45,6 +86,39 @@ pub struct Media {
    pub elapsed: Duration,
    pub length: Duration,
    pub paused: bool,
+   pub rate: f64,
+   pub sampled: Instant,
+   pub volume: f64,
+   pub shuffle: bool,
+   pub looping: String,
+   pub track_n: i32,
+   pub disc_n: i32,
+   pub album_artist: String,
+   pub genre: String,
+   pub url: String,
+   pub can_play: bool,
+   pub can_pause: bool,
+   pub can_next: bool,
+   pub can_prev: bool,
+   pub can_seek: bool,
+   pub can_control: bool,
+   pub bus: String,
+   pub desktop: Option<String>,
+}
+
+impl Media {
+   // stays frozen while paused and never runs past `length`
+   pub fn position(&self) -> Duration {
+      if self.paused {
+         return self.elapsed;
+      }
+      let drift = self.sampled.elapsed().mul_f64(self.rate.max(0.0));
+      let pos = self.elapsed + drift;
+      match self.length.is_zero() {
+         true => pos,
+         _ => pos.min(self.length),
+      }
+   }
 }
 
 pub struct INFO {
@@ -52,6 +126,7 @@ pub struct INFO {
    pub prod_read: ProductReadout,
    pub sys: System,
    pub sys_disks: Disks,
+   pub networks: Networks,
    pub player: Option<PlayerFinder>,
 
    pub user: String,
@@ -61,6 +136,8 @@ pub struct INFO {
    pub uptime: u64,
    pub os_n: String,
    pub os_v: String,
+   pub os_id: String,
+   pub os_like: Vec<String>,
    pub kern: String,
    pub log_m: String,
    pub desk_e: Option<String>,
@@ -81,6 +158,10 @@ pub struct INFO {
    pub vram: Mem,
    pub disks: Vec<Disk>,
    pub media: Vec<Media>,
+   pub history: HISTORY,
+   pub timings: Vec<(&'static str, Duration)>,
+   pub own_cpu: f32,
+   pub own_rss: u64,
 }
 
 pub trait VecMedia {
@@ -116,6 +197,7 @@ impl INFO {
       let prod_read = ProductReadout::new();
       let mut sys = System::new_all();
       let mut sys_disks = Disks::new_with_refreshed_list();
+      let networks = Networks::new_with_refreshed_list();
       let player = match PlayerFinder::new() {
          Ok(pf) => Some(pf),
          _ => None,
@@ -123,6 +205,7 @@ impl INFO {
       let (user, host, uptime) = get_user_host_uptime(&gen_read);
       let (device, bios) = get_dev_bios(&prod_read);
       let (os_n, os_v, kern) = get_os_kern();
+      let (os_id, os_like) = get_os_id();
       let (log_m, win_m, win_p, desk_e) = get_managers(&gen_read);
       let (term, shell, text_e) = get_tools(&gen_read);
       let (cpu_n, cpu_c) = get_cpu(&gen_read);
@@ -132,11 +215,12 @@ impl INFO {
       let media = get_media(&player);
       let comp = settings.vars().comp().to_string();
 
-      Self {
+      let mut info = Self {
          gen_read,
          prod_read,
          sys,
          sys_disks,
+         networks,
          player,
          user,
          host,
@@ -145,6 +229,8 @@ impl INFO {
          uptime,
          os_n,
          os_v,
+         os_id,
+         os_like,
          kern,
          log_m,
          desk_e,
@@ -165,17 +251,70 @@ impl INFO {
          vram,
          disks,
          media,
-      }
+         history: HISTORY::default(),
+         timings: Vec::new(),
+         own_cpu: 0.0,
+         own_rss: 0,
+      };
+      info.sample();
+      info
    }
 
    pub fn refresh(&mut self, settings: &SETTINGS) {
+      let mut t = Instant::now();
       (self.user, self.host, self.uptime) = get_user_host_uptime(&self.gen_read);
+      let user = lap(&mut t);
       (self.term, self.shell, self.text_e) = get_tools(&self.gen_read);
+      let tools = lap(&mut t);
+      // ram comes from sys, which is otherwise never refreshed after startup
+      self.sys.refresh_memory();
       (self.cpu_u, self.cpu_t, self.ram) = get_cpu_stats(&self.gen_read, &self.sys);
+      let cpu = lap(&mut t);
       (self.gpu_n, self.gpu_f, self.gpu_t, self.vram) = get_gpu_stats();
+      let gpu = lap(&mut t);
       self.disks = get_disks(&mut self.sys_disks);
+      let disks = lap(&mut t);
       self.media = get_media(&self.player);
+      let media = lap(&mut t);
       self.comp = settings.vars().comp().to_string();
+      self.sample();
+      let history = lap(&mut t);
+      (self.own_cpu, self.own_rss) = get_own_usage(&mut self.sys);
+      let own = lap(&mut t);
+      self.timings = vec![
+         ("user", user),
+         ("tools", tools),
+         ("cpu", cpu),
+         ("gpu", gpu),
+         ("disks", disks),
+         ("media", media),
+         ("history", history),
+         ("self", own),
+      ];
+   }
+
+   // disks must be refreshed already
+   fn sample(&mut self) {
+      self.networks.refresh(true);
+      let (net_rx, net_tx) = self.networks.values().fold((0, 0), |(rx, tx), n| {
+         (rx + n.received(), tx + n.transmitted())
+      });
+      let (disk_r, disk_w) = self.sys_disks.list().iter().fold((0, 0), |(r, w), d| {
+         let usage = d.usage();
+         (r + usage.read_bytes, w + usage.written_bytes)
+      });
+      self.history.record(Sample {
+         cpu_u: self.cpu_u as f64,
+         cpu_t: self.cpu_t as f64,
+         ram: self.ram.percent(),
+         gpu_t: self.gpu_t as f64,
+         gpu_f: self.gpu_f as f64,
+         vram: self.vram.percent(),
+         net_rx,
+         net_tx,
+         disk_r,
+         disk_w,
+      });
    }
 
    pub fn to_lua(&self, lua: &mlua::Lua) -> LuaResult<LuaTable> {
@@ -187,6 +326,8 @@ impl INFO {
       table.set("uptime", self.uptime)?;
       table.set("os_n", &*self.os_n)?;
       table.set("os_v", &*self.os_v)?;
+      table.set("os_id", &*self.os_id)?;
+      table.set("os_like", self.os_like.clone())?;
       table.set("kern", &*self.kern)?;
       table.set("log_m", &*self.log_m)?;
       table.set("desk_e", self.desk_e.clone())?;
@@ -227,6 +368,7 @@ impl INFO {
          disks.set(i + 1, disk)?;
       }
       table.set("disks", disks)?;
+      table.set("history", self.history.to_lua(lua)?)?;
 
       let media_list = lua.create_table()?;
       for (i, m) in self.media.iter().enumerate() {
@@ -237,8 +379,26 @@ impl INFO {
          media.set("album", &*m.album)?;
          media.set("art_url", &*m.art_url)?;
          media.set("elapsed", m.elapsed.as_secs())?;
+         media.set("position", m.position().as_secs_f64())?;
          media.set("length", m.length.as_secs())?;
          media.set("paused", m.paused)?;
+         media.set("rate", m.rate)?;
+         media.set("volume", m.volume)?;
+         media.set("shuffle", m.shuffle)?;
+         media.set("looping", &*m.looping)?;
+         media.set("track_n", m.track_n)?;
+         media.set("disc_n", m.disc_n)?;
+         media.set("album_artist", &*m.album_artist)?;
+         media.set("genre", &*m.genre)?;
+         media.set("url", &*m.url)?;
+         media.set("can_play", m.can_play)?;
+         media.set("can_pause", m.can_pause)?;
+         media.set("can_next", m.can_next)?;
+         media.set("can_prev", m.can_prev)?;
+         media.set("can_seek", m.can_seek)?;
+         media.set("can_control", m.can_control)?;
+         media.set("bus", &*m.bus)?;
+         media.set("desktop", m.desktop.clone())?;
          media_list.set(i + 1, media)?;
       }
       table.set("media", media_list)?;
@@ -277,6 +437,15 @@ fn get_os_kern() -> (String, String, String) {
    (os_n, os_v, kern)
 }
 
+fn get_os_id() -> (String, Vec<String>) {
+   let os_id = System::distribution_id().to_lowercase();
+   let os_like = System::distribution_id_like()
+      .into_iter()
+      .map(|id| id.to_lowercase())
+      .collect();
+   (os_id, os_like)
+}
+
 fn get_managers(gen_read: &GeneralReadout) -> (String, String, String, Option<String>) {
    let log_path = "/etc/systemd/system/display-manager.service";
    let log_m = fs::read_link(log_path.to_string())
@@ -341,6 +510,26 @@ fn get_cpu(gen_read: &GeneralReadout) -> (String, u8) {
    (cpu_n, cpu_c)
 }
 
+fn lap(start: &mut Instant) -> Duration {
+   let now = Instant::now();
+   let spent = now - *start;
+   *start = now;
+   spent
+}
+
+// the cpu reading needs two refreshes
+fn get_own_usage(sys: &mut System) -> (f32, u64) {
+   let pid = match sysinfo::get_current_pid() {
+      Ok(pid) => pid,
+      _ => return (0.0, 0),
+   };
+   sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+   match sys.process(pid) {
+      Some(p) => (p.cpu_usage(), p.memory()),
+      None => (0.0, 0),
+   }
+}
+
 fn get_cpu_stats(gen_read: &GeneralReadout, sys: &System) -> (u8, f32, Mem) {
    let cpu_u = gen_read.cpu_usage().unwrap_or(0) as u8;
    let mut cpu_t: f32 = 0.0;
@@ -433,6 +622,18 @@ fn get_disks(sys_disks: &mut Disks) -> Vec<Disk> {
    disks
 }
 
+fn join_or_default(list: Option<Vec<&str>>) -> String {
+   list
+      .and_then(|a| {
+         if a.is_empty() {
+            None
+         } else {
+            Some(a.join(", "))
+         }
+      })
+      .unwrap_or(DEFAULT.into())
+}
+
 fn get_media(pf: &Option<PlayerFinder>) -> Vec<Media> {
    let players = match pf {
       Some(pf) => match pf.find_all() {
@@ -449,25 +650,39 @@ fn get_media(pf: &Option<PlayerFinder>) -> Vec<Media> {
             match m {
                Ok(m) => {
                   let song = m.title().unwrap_or(DEFAULT).to_string();
-                  let artist = m
-                     .artists()
-                     .and_then(|a| {
-                        if a.is_empty() {
-                           None
-                        } else {
-                           Some(a.join(", "))
-                        }
-                     })
-                     .unwrap_or(DEFAULT.into());
+                  let artist = join_or_default(m.artists());
+                  let album_artist = join_or_default(m.album_artists());
+                  let genre = join_or_default(m.get("xesam:genre").and_then(|g| g.as_str_array()));
                   let name = player.identity().to_lowercase();
                   let album = m.album_name().unwrap_or(DEFAULT).to_string();
                   let art_url = m.art_url().unwrap_or(DEFAULT).to_string();
+                  let url = m.url().unwrap_or(DEFAULT).to_string();
+                  let track_n = m.track_number().unwrap_or(0);
+                  let disc_n = m.disc_number().unwrap_or(0);
                   let length = m.length().unwrap_or(Duration::from_micros(0));
                   let elapsed = player.get_position().unwrap_or(Duration::from_micros(0));
+                  let sampled = Instant::now();
                   let paused = player
                      .get_playback_status()
-                     .map(|s| s == mpris::PlaybackStatus::Paused)
+                     .map(|s| s != mpris::PlaybackStatus::Playing)
                      .unwrap_or(true);
+                  let rate = player.get_playback_rate().unwrap_or(1.0);
+                  let volume = player.get_volume().unwrap_or(0.0);
+                  let shuffle = player.get_shuffle().unwrap_or(false);
+                  let looping = match player.get_loop_status() {
+                     Ok(LoopStatus::Track) => "track",
+                     Ok(LoopStatus::Playlist) => "playlist",
+                     _ => "none",
+                  }
+                  .to_string();
+                  let can_play = player.can_play().unwrap_or(false);
+                  let can_pause = player.can_pause().unwrap_or(false);
+                  let can_next = player.can_go_next().unwrap_or(false);
+                  let can_prev = player.can_go_previous().unwrap_or(false);
+                  let can_seek = player.can_seek().unwrap_or(false);
+                  let can_control = player.can_control().unwrap_or(false);
+                  let bus = player.bus_name().to_string();
+                  let desktop = player.get_desktop_entry().unwrap_or(None);
                   let media = Media {
                      player,
                      name,
@@ -478,6 +693,24 @@ fn get_media(pf: &Option<PlayerFinder>) -> Vec<Media> {
                      elapsed,
                      length,
                      paused,
+                     rate,
+                     sampled,
+                     volume,
+                     shuffle,
+                     looping,
+                     track_n,
+                     disc_n,
+                     album_artist,
+                     genre,
+                     url,
+                     can_play,
+                     can_pause,
+                     can_next,
+                     can_prev,
+                     can_seek,
+                     can_control,
+                     bus,
+                     desktop,
                   };
                   medias.push(media);
                }
@@ -0,0 +1,122 @@
+use katatui::mlua;
+use katatui::mlua::prelude::{LuaResult, LuaTable};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+pub const HISTORY_LEN: usize = 240;
+
+#[derive(Debug, Clone)]
+pub struct Ring {
+   values: VecDeque<f64>,
+}
+
+impl Default for Ring {
+   fn default() -> Self {
+      Self {
+         values: VecDeque::with_capacity(HISTORY_LEN),
+      }
+   }
+}
+
+impl Ring {
+   pub fn push(&mut self, value: f64) {
+      if self.values.len() == HISTORY_LEN {
+         self.values.pop_front();
+      }
+      self.values.push_back(value);
+   }
+
+   pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+      self.values.iter().copied()
+   }
+
+   fn to_lua(&self, lua: &mlua::Lua) -> LuaResult<LuaTable> {
+      lua.create_sequence_from(self.iter())
+   }
+}
+
+#[derive(Debug, Default)]
+pub struct Sample {
+   pub cpu_u: f64,
+   pub cpu_t: f64,
+   pub ram: f64,
+   pub gpu_t: f64,
+   pub gpu_f: f64,
+   pub vram: f64,
+   pub net_rx: u64,
+   pub net_tx: u64,
+   pub disk_r: u64,
+   pub disk_w: u64,
+}
+
+#[derive(Debug)]
+pub struct HISTORY {
+   pub cpu_u: Ring,
+   pub cpu_t: Ring,
+   pub ram: Ring,
+   pub gpu_t: Ring,
+   pub gpu_f: Ring,
+   pub vram: Ring,
+   pub net_rx: Ring,
+   pub net_tx: Ring,
+   pub disk_r: Ring,
+   pub disk_w: Ring,
+   last: Option<Instant>,
+}
+
+impl Default for HISTORY {
+   fn default() -> Self {
+      Self {
+         cpu_u: Ring::default(),
+         cpu_t: Ring::default(),
+         ram: Ring::default(),
+         gpu_t: Ring::default(),
+         gpu_f: Ring::default(),
+         vram: Ring::default(),
+         net_rx: Ring::default(),
+         net_tx: Ring::default(),
+         disk_r: Ring::default(),
+         disk_w: Ring::default(),
+         last: None,
+      }
+   }
+}
+
+impl HISTORY {
+   // the first sample has no previous one, so its rates are 0
+   pub fn record(&mut self, sample: Sample) {
+      let secs = self.last.map(|t| t.elapsed().as_secs_f64().max(0.001));
+      self.last = Some(Instant::now());
+      let rate = |bytes: u64| match secs {
+         Some(secs) => bytes as f64 / secs,
+         None => 0.0,
+      };
+
+      self.cpu_u.push(sample.cpu_u);
+      self.cpu_t.push(sample.cpu_t);
+      self.ram.push(sample.ram);
+      self.gpu_t.push(sample.gpu_t);
+      self.gpu_f.push(sample.gpu_f);
+      self.vram.push(sample.vram);
+      self.net_rx.push(rate(sample.net_rx));
+      self.net_tx.push(rate(sample.net_tx));
+      self.disk_r.push(rate(sample.disk_r));
+      self.disk_w.push(rate(sample.disk_w));
+   }
+
+   pub fn to_lua(&self, lua: &mlua::Lua) -> LuaResult<LuaTable> {
+      let table = lua.create_table()?;
+      table.set("len", HISTORY_LEN)?;
+      table.set("cpu_u", self.cpu_u.to_lua(lua)?)?;
+      table.set("cpu_t", self.cpu_t.to_lua(lua)?)?;
+      table.set("ram", self.ram.to_lua(lua)?)?;
+      table.set("gpu_t", self.gpu_t.to_lua(lua)?)?;
+      table.set("gpu_f", self.gpu_f.to_lua(lua)?)?;
+      table.set("vram", self.vram.to_lua(lua)?)?;
+      table.set("net_rx", self.net_rx.to_lua(lua)?)?;
+      table.set("net_tx", self.net_tx.to_lua(lua)?)?;
+      table.set("disk_r", self.disk_r.to_lua(lua)?)?;
+      table.set("disk_w", self.disk_w.to_lua(lua)?)?;
+      Ok(table)
+   }
+}
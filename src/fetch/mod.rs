@@ -1,2 +1,4 @@
 mod fetch;
+mod history;
 pub use fetch::*;
+pub use history::*;
@@ -1,8 +1,46 @@
 mod app;
+mod art;
 mod fetch;
 
-use app::LoopFetch;
+use app::{CLI, HELP, LoopFetch, cli, set_cli};
+use art::probe_graphics;
+use fetch::MODULES;
 
 fn main() {
+   let args = match CLI::parse(std::env::args().skip(1)) {
+      Ok(args) => args,
+      Err(e) => {
+         eprintln!("loopfetch: {e}");
+         std::process::exit(2);
+      }
+   };
+   if args.help {
+      print!("{HELP}");
+      return;
+   }
+   if args.version {
+      println!("loopfetch {}", env!("CARGO_PKG_VERSION"));
+      return;
+   }
+   if args.list_modules {
+      for (name, about) in MODULES {
+         println!("{name:<8} {about}");
+      }
+      return;
+   }
+   set_cli(args);
+
+   if cli().once {
+      match LoopFetch::once(cli()) {
+         Ok(out) => print!("{out}"),
+         Err(e) => {
+            eprintln!("loopfetch: {e}");
+            std::process::exit(1);
+         }
+      }
+      return;
+   }
+   // the terminal can only be asked while nothing else reads stdin
+   probe_graphics();
    katatui::TUI::<LoopFetch>::run();
 }
@@ -0,0 +1,31 @@
+local c = {
+    RED = "#FB4934",
+    GREEN = "#B8BB26",
+    YELLOW = "#FABD2F",
+    BLUE = "#83A598",
+    PURPLE = "#D3869B",
+    AQUA = "#8EC07C",
+    ORANGE = "#FE8019",
+    FG = "#EBDBB2",
+    GRAY = "#928374",
+}
+
+return {
+    colors = c,
+    styles = {
+        pastel1 = { fg = c.RED, bold = true },
+        pastel2 = { fg = c.FG },
+        pastel3 = { fg = c.BLUE, bold = true },
+        pastel4 = { fg = c.FG },
+        pastel5 = { fg = c.AQUA, bold = true },
+        pastel6 = { fg = c.FG },
+        pastel7 = { fg = c.PURPLE, bold = true },
+        pastel8 = { fg = c.FG },
+        pastel9 = { fg = c.YELLOW, bold = true },
+        pastel10 = { fg = c.FG },
+        pastel11 = { fg = c.GRAY },
+        pastel12 = { fg = c.ORANGE },
+        border = { fg = c.GRAY, bold = true },
+        rainbow = { rainbow = true, speed = 90, bold = true },
+    },
+}
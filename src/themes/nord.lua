@@ -0,0 +1,32 @@
+local c = {
+    FROST1 = "#8FBCBB",
+    FROST2 = "#88C0D0",
+    FROST3 = "#81A1C1",
+    FROST4 = "#5E81AC",
+    SNOW = "#ECEFF4",
+    RED = "#BF616A",
+    YELLOW = "#EBCB8B",
+    GREEN = "#A3BE8C",
+    PURPLE = "#B48EAD",
+    GRAY = "#4C566A",
+}
+
+return {
+    colors = c,
+    styles = {
+        pastel1 = { fg = c.FROST2, bold = true },
+        pastel2 = { fg = c.SNOW },
+        pastel3 = { fg = c.FROST3, bold = true },
+        pastel4 = { fg = c.SNOW },
+        pastel5 = { fg = c.FROST1, bold = true },
+        pastel6 = { fg = c.SNOW },
+        pastel7 = { fg = c.FROST4, bold = true },
+        pastel8 = { fg = c.YELLOW },
+        pastel9 = { fg = c.GREEN, bold = true },
+        pastel10 = { fg = c.RED },
+        pastel11 = { fg = c.GRAY },
+        pastel12 = { fg = c.PURPLE },
+        border = { fg = c.FROST3, bold = true },
+        rainbow = { rainbow = true, speed = 90, bold = true },
+    },
+}
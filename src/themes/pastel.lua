@@ -0,0 +1,35 @@
+-- the default look, same colors as the stock init.lua
+local c = {
+    PINK = "#FFB6C1",
+    GREEN = "#77DD77",
+    BLUE = "#AEC6CF",
+    ORANGE = "#FFCC99",
+    CYAN = "#B2FFFF",
+    LIME = "#CCFF99",
+    PURPLE = "#CDA4DE",
+    GOLD = "#FFD700",
+    TEAL = "#99EEDD",
+    MAGENTA = "#FF99CC",
+    SILVER = "#C0C0C0",
+    WHITE = "#FFFFFF",
+}
+
+return {
+    colors = c,
+    styles = {
+        pastel1 = { fg = c.PINK, bold = true, italic = true },
+        pastel2 = { fg = c.GREEN, bold = true },
+        pastel3 = { fg = c.BLUE, italic = true },
+        pastel4 = { fg = c.ORANGE, bold = true },
+        pastel5 = { fg = c.CYAN },
+        pastel6 = { fg = c.LIME, italic = true },
+        pastel7 = { fg = c.PURPLE, bold = true },
+        pastel8 = { fg = c.GOLD },
+        pastel9 = { fg = c.TEAL, italic = true },
+        pastel10 = { fg = c.MAGENTA, bold = true, italic = true },
+        pastel11 = { fg = c.SILVER },
+        pastel12 = { fg = c.ORANGE, bold = true },
+        border = { fg = c.WHITE, bold = true },
+        rainbow = { rainbow = true, speed = 90, bold = true },
+    },
+}